@@ -0,0 +1,179 @@
+use std::str::FromStr;
+
+use rand::Rng;
+
+use crate::{
+    entity::Entity,
+    shot::{Shooter, SmashMode},
+    utils,
+};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "easy" => Ok(Difficulty::Easy),
+            "normal" => Ok(Difficulty::Normal),
+            "hard" => Ok(Difficulty::Hard),
+            _ => Err(format!(
+                "unknown difficulty `{}` (expected easy, normal or hard)",
+                value
+            )),
+        }
+    }
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        }
+    }
+
+    /// Chance of going for a smash on each return
+    fn smash_chance(self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.1,
+            Difficulty::Normal => 0.3,
+            Difficulty::Hard => 0.6,
+        }
+    }
+
+    /// How fast AI paddles get around compared to a player's, where there are no smashes
+    pub fn paddle_speed(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.7,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.3,
+        }
+    }
+
+    /// Most frames the AI's shot can be mistimed by
+    fn timing_error(self) -> i32 {
+        match self {
+            Difficulty::Easy => 12,
+            Difficulty::Normal => 6,
+            Difficulty::Hard => 2,
+        }
+    }
+}
+
+// How many frames before the hit the AI aims to press shoot in timing mode
+const PRESS_LEAD: i32 = 3;
+
+/// Frames from now that the AI will press and release shoot
+struct ShotPlan {
+    press_in: Option<i32>,
+    release_in: Option<i32>,
+}
+
+/// Decides when the AI goes for a smash, pressing shoot the same way the player does
+pub struct AiShooter {
+    difficulty: Difficulty,
+    charge_frames: u32,
+    approaching: bool,
+    plan: Option<ShotPlan>,
+}
+
+impl AiShooter {
+    pub fn new(difficulty: Difficulty, charge_frames: u32) -> AiShooter {
+        AiShooter {
+            difficulty,
+            charge_frames,
+            approaching: false,
+            plan: None,
+        }
+    }
+
+    /// Returns the smash power if the AI shot just late enough to smash
+    pub fn update(&mut self, ai: &Entity, ball: &Entity, shooter: &mut Shooter) -> Option<f32> {
+        let toward_ai = (ai.position.x - ball.position.x).signum() == ball.velocity.x.signum()
+            && ball.velocity.x != 0.0;
+
+        if toward_ai && !self.approaching {
+            // Decide once per approach whether to go for it
+            let mut rng = utils::rng();
+            if rng.gen_bool(self.difficulty.smash_chance()) {
+                let distance = (ai.position.x - ball.position.x).abs()
+                    - (ai.half_size.x + ball.half_size.x) as f32;
+                let frames_to_hit = (distance / ball.velocity.x.abs()) as i32;
+                let error = self.difficulty.timing_error();
+                let jitter = rng.gen_range(-error..=error);
+
+                self.plan = Some(match shooter.mode() {
+                    SmashMode::Timing => ShotPlan {
+                        press_in: Some(frames_to_hit - PRESS_LEAD + jitter),
+                        release_in: None,
+                    },
+                    SmashMode::Charge => ShotPlan {
+                        press_in: Some(frames_to_hit - self.charge_frames as i32),
+                        release_in: Some(frames_to_hit + jitter),
+                    },
+                });
+            }
+        }
+        self.approaching = toward_ai;
+
+        let plan = self.plan.as_mut()?;
+        let mut smash = None;
+
+        if let Some(press_in) = plan.press_in.as_mut() {
+            *press_in -= 1;
+            if *press_in <= 0 {
+                smash = shooter.press();
+                plan.press_in = None;
+            }
+        }
+        if let Some(release_in) = plan.release_in.as_mut() {
+            *release_in -= 1;
+            if *release_in <= 0 {
+                smash = smash.or(shooter.release());
+                plan.release_in = None;
+            }
+        }
+
+        if plan.press_in.is_none() && plan.release_in.is_none() {
+            self.plan = None;
+        }
+
+        smash
+    }
+
+    pub fn reset(&mut self) {
+        self.approaching = false;
+        self.plan = None;
+    }
+}
+
+/// Steers a paddle towards the ball, slowing down once the ball is within its reach
+pub fn track_ball(paddle: &mut Entity, ball: &Entity, max_speed: f32) {
+    let ball_overlap = Entity::get_overlap(paddle, ball);
+    if ball_overlap.y <= ball.size.y as i32 {
+        if ball.position.y < paddle.position.y {
+            paddle.acceleration.y = -1.0;
+        } else {
+            paddle.acceleration.y = 1.0;
+        }
+        paddle.velocity.y += paddle.acceleration.y;
+    } else {
+        let velocity_sign = paddle.velocity.y.signum();
+        paddle.acceleration.y = -velocity_sign;
+        paddle.velocity.y += paddle.acceleration.y;
+        paddle.velocity.y = paddle.velocity.y.abs().clamp(0.0, max_speed) * velocity_sign;
+        if paddle.velocity.y.abs() < 2.5 {
+            paddle.velocity.y = 0.0;
+        }
+    }
+}
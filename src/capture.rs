@@ -0,0 +1,242 @@
+//! Exporting matches as an animated GIF or a sequence of PNG frames
+
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter},
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use sfml::{
+    graphics::Color,
+    system::{Vector2, Vector2f, Vector2i, Vector2u},
+    window::Event,
+};
+
+use crate::{
+    error::GameError,
+    gif::GifWriter,
+    png,
+    render::{offscreen::OffscreenRenderer, Renderer, Scene},
+    replay,
+};
+
+// GIF delays are in hundredths of a second and most viewers ignore anything under two
+const MIN_FRAME_MS: u64 = 20;
+
+enum Output {
+    Gif {
+        writer: GifWriter<BufWriter<File>>,
+        // Frame waiting for the next one to know how long it stays on screen
+        pending: Option<(Vec<u8>, u64)>,
+    },
+    Frames {
+        directory: PathBuf,
+        count: u32,
+    },
+}
+
+/// Renders scenes offscreen and writes them out, to a GIF if the path ends in `.gif` and
+/// as numbered PNGs in a directory otherwise
+pub struct Capture {
+    renderer: OffscreenRenderer,
+    output: Output,
+}
+
+impl Capture {
+    pub fn new(
+        path: &Path,
+        desktop_size: Vector2u,
+        image_size: Vector2u,
+        backdrop: Color,
+    ) -> io::Result<Capture> {
+        let is_gif = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("gif"));
+
+        let output = if is_gif {
+            Output::Gif {
+                writer: GifWriter::new(BufWriter::new(File::create(path)?), image_size)?,
+                pending: None,
+            }
+        } else {
+            fs::create_dir_all(path)?;
+            Output::Frames {
+                directory: path.to_path_buf(),
+                count: 0,
+            }
+        };
+
+        Ok(Capture {
+            renderer: OffscreenRenderer::new(desktop_size, image_size, backdrop),
+            output,
+        })
+    }
+
+    /// Adds a frame shown `timestamp` milliseconds after the start of the match
+    pub fn add_frame(&mut self, scene: &Scene, timestamp: u64) -> io::Result<()> {
+        self.renderer.render(scene);
+        let pixels = self.renderer.pixels();
+
+        match &mut self.output {
+            Output::Gif { writer, pending } => match pending.take() {
+                Some((_, start)) if timestamp < start + MIN_FRAME_MS => {
+                    // Too soon after the last frame, keep the newer picture in its slot
+                    *pending = Some((pixels.to_vec(), start));
+                }
+                Some((previous, start)) => {
+                    let delay = ((timestamp - start) / 10).min(u16::MAX as u64) as u16;
+                    writer.write_frame(&previous, delay)?;
+                    *pending = Some((pixels.to_vec(), timestamp));
+                }
+                None => *pending = Some((pixels.to_vec(), timestamp)),
+            },
+            Output::Frames { directory, count } => {
+                let path = directory.join(format!("frame_{:05}.png", count));
+                fs::write(path, png::encode(self.renderer.image_size(), pixels))?;
+                *count += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn finish(self) -> io::Result<()> {
+        match self.output {
+            Output::Gif {
+                mut writer,
+                pending,
+            } => {
+                if let Some((pixels, _)) = pending {
+                    writer.write_frame(&pixels, (MIN_FRAME_MS / 10) as u16)?;
+                }
+                writer.finish()
+            }
+            Output::Frames { .. } => Ok(()),
+        }
+    }
+}
+
+/// Captures every frame drawn by another renderer while the match is played
+pub struct CaptureRenderer {
+    inner: Box<dyn Renderer>,
+    capture: Option<Capture>,
+    start: Instant,
+}
+
+impl CaptureRenderer {
+    pub fn new(inner: Box<dyn Renderer>, capture: Capture) -> CaptureRenderer {
+        CaptureRenderer {
+            inner,
+            capture: Some(capture),
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Renderer for CaptureRenderer {
+    fn render(&mut self, scene: &Scene) {
+        self.inner.render(scene);
+
+        if let Some(capture) = self.capture.as_mut() {
+            let timestamp = self.start.elapsed().as_millis() as u64;
+            if let Err(err) = capture.add_frame(scene, timestamp) {
+                println!("Stopped capturing the match: {}", err);
+                self.capture = None;
+            }
+        }
+    }
+
+    fn open(&mut self, scene: &Scene) -> Result<(), GameError> {
+        self.inner.open(scene)
+    }
+
+    fn poll_events(&mut self) -> Vec<Event> {
+        self.inner.poll_events()
+    }
+
+    fn mouse_position(&self) -> Option<Vector2f> {
+        self.inner.mouse_position()
+    }
+
+    fn dragged_position(&mut self, name: &str) -> Option<Vector2i> {
+        self.inner.dragged_position(name)
+    }
+
+    fn closed_windows(&mut self) -> Vec<String> {
+        self.inner.closed_windows()
+    }
+
+    fn set_input_window(&mut self, name: &str) {
+        self.inner.set_input_window(name)
+    }
+}
+
+impl Drop for CaptureRenderer {
+    fn drop(&mut self) {
+        if let Some(capture) = self.capture.take() {
+            if let Err(err) = capture.finish() {
+                println!("Couldn't finish the capture: {}", err);
+            }
+        }
+    }
+}
+
+/// Picks the capture resolution from a `WxH` or `W` argument, keeping the desktop's
+/// aspect ratio when only the width is given
+pub fn parse_size(value: Option<&str>, desktop_size: Vector2u) -> Result<Vector2u, String> {
+    let aspect_height =
+        |width: u32| (width as u64 * desktop_size.y as u64 / desktop_size.x.max(1) as u64) as u32;
+
+    let size = match value {
+        None => Vector2::new(640, aspect_height(640)),
+        Some(value) => {
+            let invalid = || format!("invalid capture size `{}` (expected WxH)", value);
+            match value.split_once('x') {
+                Some((width, height)) => Vector2::new(
+                    width.parse().map_err(|_| invalid())?,
+                    height.parse().map_err(|_| invalid())?,
+                ),
+                None => {
+                    let width = value.parse().map_err(|_| invalid())?;
+                    Vector2::new(width, aspect_height(width))
+                }
+            }
+        }
+    };
+
+    if size.x == 0 || size.y == 0 || size.x > u16::MAX as u32 || size.y > u16::MAX as u32 {
+        return Err(format!(
+            "capture size {}x{} is out of range",
+            size.x, size.y
+        ));
+    }
+    Ok(size)
+}
+
+/// Renders a recorded replay file to a GIF or PNG frames, no display needed
+pub fn export(
+    replay_path: &Path,
+    output: &Path,
+    size: Option<&str>,
+    backdrop: Color,
+) -> Result<(), String> {
+    let replay = replay::load(replay_path)
+        .map_err(|err| format!("Couldn't read {}: {}", replay_path.display(), err))?;
+    let image_size = parse_size(size, replay.desktop_size)?;
+
+    let write_error = |err: io::Error| format!("Couldn't write {}: {}", output.display(), err);
+    let mut capture =
+        Capture::new(output, replay.desktop_size, image_size, backdrop).map_err(write_error)?;
+    for (timestamp, scene) in &replay.frames {
+        capture.add_frame(scene, *timestamp).map_err(write_error)?;
+    }
+    capture.finish().map_err(write_error)?;
+
+    println!(
+        "Exported {} frames to {}",
+        replay.frames.len(),
+        output.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,396 @@
+//! Command line parsing, by hand to keep the dependencies down
+
+use std::path::PathBuf;
+
+use sfml::system::{Vector2, Vector2u};
+
+use crate::{ai::Difficulty, modes::Mode};
+
+pub const USAGE: &str = "\
+Usage: window-pong [command] [options]
+
+Commands:
+  play                      Play a match (the default)
+  simulate                  Play AI against AI without a display, as fast as possible
+  replay <file>             Watch a recorded match, or export it with --export
+  stats [name]              Print the stats of one player, or everyone
+  records                   Print the records table
+  config validate [file]    Check a config file, the usual one by default
+
+Play options:
+  --mode <mode>             Game mode: classic, four_player, multiball, practice or time_attack
+  --difficulty <level>      AI difficulty: easy, normal or hard
+  --seed <number>           Seed the randomness to repeat a match
+  --config <file>           Read settings from another config file
+  --arena <WxH>             Play area size, the desktop by default
+  --profile <name>          Player whose stats are kept
+  --single-window           Draw everything in one resizable window
+  --fullscreen              Draw everything in one fullscreen window
+  --record <file>           Save a replay of the match
+  --capture <file|dir>      Capture the match to a GIF, or PNG frames in a directory
+  --capture-size <WxH>      Size of captured frames, 640 pixels wide by default
+
+Simulate options:
+  --matches <number>        Matches to play, 1 by default
+  and --mode, --difficulty, --seed, --config and --arena from play
+
+Replay options:
+  --export <file|dir>       Render to a GIF or PNG frames instead of showing it
+  --capture-size <WxH>      Size of exported frames
+  and --config, --single-window and --fullscreen from play
+
+  -h, --help                Print this help
+  -V, --version             Print the version";
+
+#[derive(Default)]
+pub struct PlayOptions {
+    pub mode: Option<Mode>,
+    pub difficulty: Option<Difficulty>,
+    pub seed: Option<u64>,
+    pub config: Option<PathBuf>,
+    pub arena: Option<Vector2u>,
+    pub profile: Option<String>,
+    pub single_window: bool,
+    pub fullscreen: bool,
+    pub record: Option<PathBuf>,
+    pub capture: Option<PathBuf>,
+    pub capture_size: Option<String>,
+}
+
+pub enum Command {
+    Play(PlayOptions),
+    Simulate {
+        options: PlayOptions,
+        matches: u32,
+    },
+    Replay {
+        path: PathBuf,
+        export: Option<PathBuf>,
+        options: PlayOptions,
+    },
+    Stats {
+        name: Option<String>,
+    },
+    Records,
+    ValidateConfig {
+        path: Option<PathBuf>,
+    },
+    Help,
+    Version,
+}
+
+/// Parses `WxH` sizes
+pub fn parse_size(value: &str) -> Result<Vector2u, String> {
+    let error = || format!("invalid size `{}` (expected WxH, like 1920x1080)", value);
+    let (width, height) = value.split_once('x').ok_or_else(error)?;
+    let size = Vector2::new(
+        width.parse().map_err(|_| error())?,
+        height.parse().map_err(|_| error())?,
+    );
+    if size.x == 0 || size.y == 0 {
+        return Err(error());
+    }
+    Ok(size)
+}
+
+struct Args {
+    args: Vec<String>,
+    index: usize,
+}
+
+impl Args {
+    fn next(&mut self) -> Option<String> {
+        let arg = self.args.get(self.index).cloned();
+        self.index += 1;
+        arg
+    }
+
+    fn value(&mut self, flag: &str) -> Result<String, String> {
+        self.next()
+            .ok_or_else(|| format!("`{}` needs a value", flag))
+    }
+
+    fn number<T: std::str::FromStr>(&mut self, flag: &str) -> Result<T, String> {
+        let value = self.value(flag)?;
+        value
+            .parse()
+            .map_err(|_| format!("`{}` expects a number, found `{}`", flag, value))
+    }
+}
+
+const PLAY_OPTIONS: [&str; 11] = [
+    "--mode",
+    "--difficulty",
+    "--seed",
+    "--config",
+    "--arena",
+    "--profile",
+    "--single-window",
+    "--fullscreen",
+    "--record",
+    "--capture",
+    "--capture-size",
+];
+const SIMULATE_OPTIONS: [&str; 6] = [
+    "--mode",
+    "--difficulty",
+    "--seed",
+    "--config",
+    "--arena",
+    "--matches",
+];
+const REPLAY_OPTIONS: [&str; 5] = [
+    "--config",
+    "--export",
+    "--capture-size",
+    "--single-window",
+    "--fullscreen",
+];
+
+/// Reads a play option, returning whether `flag` was one
+fn play_option(flag: &str, args: &mut Args, options: &mut PlayOptions) -> Result<bool, String> {
+    match flag {
+        "--mode" => options.mode = Some(args.value(flag)?.parse()?),
+        "--difficulty" => options.difficulty = Some(args.value(flag)?.parse()?),
+        "--seed" => options.seed = Some(args.number(flag)?),
+        "--config" => options.config = Some(PathBuf::from(args.value(flag)?)),
+        "--arena" => options.arena = Some(parse_size(&args.value(flag)?)?),
+        "--profile" => options.profile = Some(args.value(flag)?),
+        "--single-window" => options.single_window = true,
+        "--fullscreen" => options.fullscreen = true,
+        "--record" => options.record = Some(PathBuf::from(args.value(flag)?)),
+        "--capture" => options.capture = Some(PathBuf::from(args.value(flag)?)),
+        "--capture-size" => options.capture_size = Some(args.value(flag)?),
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+pub fn parse(args: Vec<String>) -> Result<Command, String> {
+    let mut args = Args { args, index: 0 };
+
+    // Help and version win wherever they are
+    if args.args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        return Ok(Command::Help);
+    }
+    if args
+        .args
+        .iter()
+        .any(|arg| arg == "-V" || arg == "--version")
+    {
+        return Ok(Command::Version);
+    }
+
+    let subcommand = match args.args.first() {
+        Some(arg) if !arg.starts_with('-') => args.next(),
+        _ => None,
+    };
+
+    let mut options = PlayOptions::default();
+    let mut positional = Vec::new();
+    let mut matches = 1;
+    let mut export = None;
+    // Options given, so the ones a command doesn't use can be turned down
+    let mut given = Vec::new();
+
+    while let Some(arg) = args.next() {
+        if play_option(&arg, &mut args, &mut options)? {
+            given.push(arg);
+            continue;
+        }
+        match arg.as_str() {
+            "--matches" => {
+                matches = args.number(&arg)?;
+                if matches == 0 {
+                    return Err(String::from("`--matches` must be above 0"));
+                }
+            }
+            "--export" => export = Some(PathBuf::from(args.value(&arg)?)),
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{}`", arg)),
+            _ => {
+                positional.push(arg);
+                continue;
+            }
+        }
+        given.push(arg);
+    }
+
+    let unexpected = |positional: &[String], allowed: usize| match positional.get(allowed) {
+        Some(arg) => Err(format!("unexpected argument `{}`", arg)),
+        None => Ok(()),
+    };
+    let unused = |command: &str, allowed: &[&str]| match given
+        .iter()
+        .find(|flag| !allowed.contains(&flag.as_str()))
+    {
+        Some(flag) => Err(format!("`{}` doesn't apply to `{}`", flag, command)),
+        None => Ok(()),
+    };
+
+    match subcommand.as_deref() {
+        None | Some("play") => {
+            unexpected(&positional, 0)?;
+            unused("play", &PLAY_OPTIONS)?;
+            Ok(Command::Play(options))
+        }
+        Some("simulate") => {
+            unexpected(&positional, 0)?;
+            unused("simulate", &SIMULATE_OPTIONS)?;
+            if options.mode == Some(Mode::Practice) {
+                return Err(String::from(
+                    "practice never ends, so there's nothing to simulate",
+                ));
+            }
+            Ok(Command::Simulate { options, matches })
+        }
+        Some("replay") => {
+            unexpected(&positional, 1)?;
+            unused("replay", &REPLAY_OPTIONS)?;
+            let path = positional
+                .first()
+                .ok_or_else(|| String::from("`replay` needs a replay file"))?;
+            Ok(Command::Replay {
+                path: PathBuf::from(path),
+                export,
+                options,
+            })
+        }
+        Some("stats") => {
+            unexpected(&positional, 1)?;
+            unused("stats", &[])?;
+            Ok(Command::Stats {
+                name: positional.first().cloned(),
+            })
+        }
+        Some("records") => {
+            unexpected(&positional, 0)?;
+            unused("records", &[])?;
+            Ok(Command::Records)
+        }
+        Some("config") => match positional.first().map(String::as_str) {
+            Some("validate") => {
+                unexpected(&positional, 2)?;
+                unused("config validate", &[])?;
+                Ok(Command::ValidateConfig {
+                    path: positional.get(1).map(PathBuf::from),
+                })
+            }
+            Some(other) => Err(format!("unknown config command `{}`", other)),
+            None => Err(String::from(
+                "`config` needs a command, like `config validate`",
+            )),
+        },
+        Some(other) => Err(format!("unknown command `{}`", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, Command, Mode};
+
+    fn parse_line(line: &str) -> Result<Command, String> {
+        parse(line.split_whitespace().map(String::from).collect())
+    }
+
+    fn error(line: &str) -> String {
+        match parse_line(line) {
+            Ok(_) => panic!("`{}` should have been turned down", line),
+            Err(err) => err,
+        }
+    }
+
+    #[test]
+    fn plays_by_default() {
+        assert!(matches!(parse_line(""), Ok(Command::Play(_))));
+
+        let Ok(Command::Play(options)) =
+            parse_line("play --mode multiball --seed 7 --arena 800x600 --single-window")
+        else {
+            panic!("expected a play command");
+        };
+        assert!(options.mode == Some(Mode::Multiball));
+        assert_eq!(options.seed, Some(7));
+        assert_eq!(
+            options.arena.map(|arena| (arena.x, arena.y)),
+            Some((800, 600))
+        );
+        assert!(options.single_window);
+    }
+
+    #[test]
+    fn help_and_version_win_anywhere() {
+        assert!(matches!(parse_line("stats --seed x -h"), Ok(Command::Help)));
+        assert!(matches!(
+            parse_line("records --version"),
+            Ok(Command::Version)
+        ));
+    }
+
+    #[test]
+    fn reads_subcommands() {
+        assert!(matches!(
+            parse_line("simulate --matches 3 --mode time_attack"),
+            Ok(Command::Simulate { matches: 3, .. })
+        ));
+        assert!(matches!(
+            parse_line("replay match.replay --export out.gif --fullscreen"),
+            Ok(Command::Replay {
+                export: Some(_),
+                ..
+            })
+        ));
+        assert!(matches!(
+            parse_line("stats Sam"),
+            Ok(Command::Stats { name: Some(name) }) if name == "Sam"
+        ));
+        assert!(matches!(parse_line("records"), Ok(Command::Records)));
+        assert!(matches!(
+            parse_line("config validate pong.toml"),
+            Ok(Command::ValidateConfig { path: Some(_) })
+        ));
+    }
+
+    #[test]
+    fn turns_down_bad_values() {
+        assert_eq!(error("simulate --matches 0"), "`--matches` must be above 0");
+        assert_eq!(
+            error("simulate --matches lots"),
+            "`--matches` expects a number, found `lots`"
+        );
+        assert_eq!(error("--seed"), "`--seed` needs a value");
+        assert!(error("--mode golf").starts_with("unknown game mode `golf`"));
+        assert!(error("--arena 800").starts_with("invalid size `800`"));
+        assert!(error("simulate --mode practice").starts_with("practice never ends"));
+    }
+
+    #[test]
+    fn turns_down_options_a_command_doesnt_use() {
+        assert_eq!(error("stats --seed 3"), "`--seed` doesn't apply to `stats`");
+        assert_eq!(
+            error("records --arena 800x600"),
+            "`--arena` doesn't apply to `records`"
+        );
+        assert_eq!(
+            error("config validate --mode classic"),
+            "`--mode` doesn't apply to `config validate`"
+        );
+        assert_eq!(
+            error("simulate --record match.replay"),
+            "`--record` doesn't apply to `simulate`"
+        );
+        assert_eq!(error("--matches 3"), "`--matches` doesn't apply to `play`");
+        assert_eq!(
+            error("replay match.replay --seed 1"),
+            "`--seed` doesn't apply to `replay`"
+        );
+    }
+
+    #[test]
+    fn turns_down_unknown_arguments() {
+        assert_eq!(error("--fast"), "unknown option `--fast`");
+        assert_eq!(error("golf"), "unknown command `golf`");
+        assert_eq!(error("play now"), "unexpected argument `now`");
+        assert_eq!(error("replay"), "`replay` needs a replay file");
+        assert_eq!(error("config check"), "unknown config command `check`");
+    }
+}
@@ -0,0 +1,400 @@
+use std::{
+    env, fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{
+    ai::Difficulty,
+    effects::{EffectSettings, TrailStyle},
+    four_player::Seat,
+    game::ClosePolicy,
+    input::Control,
+    modes::practice::Drill,
+    motion::MotionPattern,
+    render::Align,
+    scoreboard::ScoreBoardLayout,
+    serve::ServeRule,
+    shot::SmashMode,
+    sizing::{PaddleSizing, ShrinkRule},
+    theme::{parse_color, Theme},
+};
+
+/// Match settings, read from `config.toml` in the window-pong config directory
+pub struct Config {
+    // Score board
+    pub score_board_motion: MotionPattern,
+    pub score_board_speed: f32,
+    pub score_board_range: f32,
+    pub score_board_layout: ScoreBoardLayout,
+
+    // Match
+    pub points_to_win: u8,
+    // Lives for each side, when above 0 sides are knocked out instead of playing to points
+    pub lives: u8,
+    // Minutes a time attack match lasts
+    pub time_limit: f32,
+
+    // Serve
+    pub serve_rule: ServeRule,
+    pub serve_ai_delay: u32,
+
+    // Smash
+    pub smash_mode: SmashMode,
+    pub smash_charge_frames: u32,
+
+    // AI
+    pub ai_difficulty: Difficulty,
+
+    // Paddle size
+    pub paddle_sizing: PaddleSizing,
+
+    // Paddle movement
+    pub free_movement: bool,
+    // Fraction of the arena's width, from each paddle's own edge, it can move around in
+    pub movement_zone: f32,
+
+    // Visual effects
+    pub effects: EffectSettings,
+
+    // Colors
+    pub theme: Theme,
+
+    // Input
+    pub control: Control,
+
+    // Windows
+    pub window_close: ClosePolicy,
+    pub pause_on_focus_loss: bool,
+
+    // Who plays the left, right, top and bottom edges in four player mode
+    pub seats: [Seat; 4],
+
+    // Practice mode
+    pub drill: Drill,
+
+    // Multiball mode, the most balls in play at once and the returns it takes to add one
+    pub max_balls: u8,
+    pub new_ball_every: u32,
+}
+
+pub struct ConfigError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Config error on line {}: {}", self.line, self.message)
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            score_board_motion: MotionPattern::Fixed,
+            score_board_speed: 4.0,
+            score_board_range: 400.0,
+            score_board_layout: ScoreBoardLayout {
+                font: None,
+                text_size: 75,
+                align: Align::Center,
+                template: String::from("{player} {score} {ai}"),
+                auto_resize: true,
+                padding: 20,
+                player_name: String::from("Player"),
+                ai_name: String::from("AI"),
+            },
+            points_to_win: 11,
+            lives: 0,
+            time_limit: 3.0,
+            serve_rule: ServeRule::Loser,
+            serve_ai_delay: 60,
+            smash_mode: SmashMode::Timing,
+            smash_charge_frames: 45,
+            ai_difficulty: Difficulty::Normal,
+            paddle_sizing: PaddleSizing {
+                rule: ShrinkRule::OverTime,
+                shrink_per_frame: 0.0001,
+                shrink_per_hit: 0.05,
+                return_growth: 0.0,
+                miss_penalty: 0.0,
+                min_scale: 0.25,
+                max_scale: 1.1,
+            },
+            free_movement: false,
+            movement_zone: 0.3,
+            effects: EffectSettings {
+                trail: TrailStyle::Streaks,
+                trail_length: 6,
+                particles: true,
+                edge_flash: true,
+            },
+            theme: Theme::default(),
+            control: Control::Keyboard,
+            window_close: ClosePolicy::Respawn,
+            pause_on_focus_loss: true,
+            seats: [Seat::Human, Seat::Ai, Seat::Ai, Seat::Ai],
+            drill: Drill::Rally,
+            max_balls: 3,
+            new_ball_every: 4,
+        }
+    }
+}
+
+impl Config {
+    /// Loads the config file, falling back to the defaults if there isn't one
+    pub fn load() -> Result<Config, String> {
+        match config_path().filter(|path| path.exists()) {
+            Some(path) => Config::load_file(&path),
+            None => Ok(Config::default()),
+        }
+    }
+
+    /// Loads a config file that has to be there, like one named on the command line
+    pub fn load_file(path: &Path) -> Result<Config, String> {
+        let source = fs::read_to_string(path)
+            .map_err(|err| format!("Couldn't read {}: {}", path.display(), err))?;
+        Config::parse(&source).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    /// Parses `key = value` lines, with `[section]` headers prefixing the keys below them
+    pub fn parse(source: &str) -> Result<Config, ConfigError> {
+        let mut config = Config::default();
+        let mut section = String::new();
+        // Picking a theme replaces every color, so single colors go on top once it's picked
+        let mut colors = Vec::new();
+        // The smallest paddle size can only be checked against the largest once both are read
+        let mut scale_line = None;
+
+        for (index, raw_line) in source.lines().enumerate() {
+            let line = strip_comment(raw_line).trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let error = |message: String| ConfigError {
+                line: index + 1,
+                message,
+            };
+
+            if line.starts_with('[') && line.ends_with(']') {
+                section = line[1..line.len() - 1].trim().to_string();
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(error(format!("expected `key = value`, found `{}`", line)));
+            };
+
+            let key = if section.is_empty() {
+                key.trim().to_string()
+            } else {
+                format!("{}.{}", section, key.trim())
+            };
+            let value = value.trim().trim_matches('"');
+
+            if key.starts_with("theme.") && key != "theme.name" {
+                colors.push((index + 1, key, value));
+                continue;
+            }
+            config.set(&key, value).map_err(error)?;
+            if key == "paddle.min_scale" || key == "paddle.max_scale" {
+                scale_line = Some(index + 1);
+            }
+        }
+
+        let sizing = &config.paddle_sizing;
+        if let (Some(line), true) = (scale_line, sizing.min_scale > sizing.max_scale) {
+            return Err(ConfigError {
+                line,
+                message: format!(
+                    "`paddle.min_scale` ({}) must be at most `paddle.max_scale` ({})",
+                    sizing.min_scale, sizing.max_scale
+                ),
+            });
+        }
+
+        for (line, key, value) in colors {
+            config
+                .set(&key, value)
+                .map_err(|message| ConfigError { line, message })?;
+        }
+
+        Ok(config)
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "score_board.motion" => self.score_board_motion = value.parse()?,
+            "score_board.speed" => self.score_board_speed = parse_number(key, value)?,
+            "score_board.range" => {
+                self.score_board_range = parse_number(key, value)?;
+                if !self.score_board_range.is_finite() || self.score_board_range <= 0.0 {
+                    return Err(format!("`{}` must be above 0, found `{}`", key, value));
+                }
+            }
+            "score_board.font" => self.score_board_layout.font = Some(PathBuf::from(value)),
+            "score_board.text_size" => {
+                self.score_board_layout.text_size = parse_number(key, value)?
+            }
+            "score_board.align" => self.score_board_layout.align = value.parse()?,
+            "score_board.layout" => self.score_board_layout.template = value.to_string(),
+            "score_board.auto_resize" => {
+                self.score_board_layout.auto_resize = parse_bool(key, value)?
+            }
+            "score_board.padding" => self.score_board_layout.padding = parse_number(key, value)?,
+            "score_board.player_name" => self.score_board_layout.player_name = value.to_string(),
+            "score_board.ai_name" => self.score_board_layout.ai_name = value.to_string(),
+            "match.points_to_win" => self.points_to_win = parse_number(key, value)?,
+            "match.lives" => self.lives = parse_number(key, value)?,
+            "match.time_limit" => {
+                self.time_limit = parse_number(key, value)?;
+                if !self.time_limit.is_finite() || self.time_limit <= 0.0 {
+                    return Err(format!("`{}` must be above 0, found `{}`", key, value));
+                }
+            }
+            "serve.rule" => self.serve_rule = value.parse()?,
+            "serve.ai_delay" => self.serve_ai_delay = parse_number(key, value)?,
+            "smash.mode" => self.smash_mode = value.parse()?,
+            "smash.charge_frames" => self.smash_charge_frames = parse_number(key, value)?,
+            "ai.difficulty" => self.ai_difficulty = value.parse()?,
+            "paddle.shrink" => self.paddle_sizing.rule = value.parse()?,
+            "paddle.shrink_per_frame" => {
+                self.paddle_sizing.shrink_per_frame = parse_number(key, value)?
+            }
+            "paddle.shrink_per_hit" => {
+                self.paddle_sizing.shrink_per_hit = parse_number(key, value)?
+            }
+            "paddle.return_growth" => self.paddle_sizing.return_growth = parse_number(key, value)?,
+            "paddle.miss_penalty" => self.paddle_sizing.miss_penalty = parse_number(key, value)?,
+            "paddle.min_scale" | "paddle.max_scale" => {
+                let scale = parse_number(key, value)?;
+                if !(scale > 0.0 && scale < f32::INFINITY) {
+                    return Err(format!("`{}` must be above 0, found `{}`", key, value));
+                }
+                if key == "paddle.min_scale" {
+                    self.paddle_sizing.min_scale = scale;
+                } else {
+                    self.paddle_sizing.max_scale = scale;
+                }
+            }
+            "paddle.free_movement" => self.free_movement = parse_bool(key, value)?,
+            "paddle.zone" => {
+                self.movement_zone = parse_number(key, value)?;
+                if !(self.movement_zone > 0.0 && self.movement_zone <= 0.5) {
+                    return Err(format!(
+                        "`{}` must be above 0 and at most 0.5, found `{}`",
+                        key, value
+                    ));
+                }
+            }
+            "effects.trail" => self.effects.trail = value.parse()?,
+            "effects.trail_length" => self.effects.trail_length = parse_number(key, value)?,
+            "effects.particles" => self.effects.particles = parse_bool(key, value)?,
+            "effects.edge_flash" => self.effects.edge_flash = parse_bool(key, value)?,
+            "theme.name" => self.theme = value.parse()?,
+            "theme.player" => self.theme.player = parse_color(key, value)?,
+            "theme.ai" => self.theme.ai = parse_color(key, value)?,
+            "theme.ball" => self.theme.ball = parse_color(key, value)?,
+            "theme.score_board" => self.theme.score_board = parse_color(key, value)?,
+            "theme.score_text" => self.theme.score_text = parse_color(key, value)?,
+            "theme.smash" => self.theme.smash = parse_color(key, value)?,
+            "theme.eye" => self.theme.eye = parse_color(key, value)?,
+            "theme.pupil" => self.theme.pupil = parse_color(key, value)?,
+            "theme.smash_pupil" => self.theme.smash_pupil = parse_color(key, value)?,
+            "theme.charge" => self.theme.charge = parse_color(key, value)?,
+            "theme.size_meter" => self.theme.size_meter = parse_color(key, value)?,
+            "theme.flash" => self.theme.flash = parse_color(key, value)?,
+            "theme.backdrop" => self.theme.backdrop = parse_color(key, value)?,
+            "input.control" => self.control = value.parse()?,
+            "window.on_close" => self.window_close = value.parse()?,
+            "window.pause_on_focus_loss" => self.pause_on_focus_loss = parse_bool(key, value)?,
+            "four_player.left" => self.seats[0] = value.parse()?,
+            "four_player.right" => self.seats[1] = value.parse()?,
+            "four_player.top" => self.seats[2] = value.parse()?,
+            "four_player.bottom" => self.seats[3] = value.parse()?,
+            "practice.drill" => self.drill = value.parse()?,
+            "multiball.balls" => self.max_balls = parse_number(key, value)?,
+            "multiball.new_ball_every" => {
+                self.new_ball_every = parse_number(key, value)?;
+                if self.new_ball_every == 0 {
+                    return Err(format!("`{}` must be above 0, found `{}`", key, value));
+                }
+            }
+            _ => return Err(format!("unknown setting `{}`", key)),
+        }
+        Ok(())
+    }
+}
+
+/// Cuts a line at the first `#` that isn't inside quotes, so colors can be written as "#rrggbb"
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    for (index, character) in line.char_indices() {
+        match character {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..index],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn parse_number<T: FromStr>(key: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("`{}` expects a number, found `{}`", key, value))
+}
+
+fn parse_bool(key: &str, value: &str) -> Result<bool, String> {
+    value
+        .parse()
+        .map_err(|_| format!("`{}` expects true or false, found `{}`", key, value))
+}
+
+/// `$WINDOW_PONG_CONFIG`, otherwise `config.toml` in the XDG config directory
+pub fn config_path() -> Option<PathBuf> {
+    if let Some(path) = env::var_os("WINDOW_PONG_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+
+    let config_dir = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+
+    Some(config_dir.join("window-pong").join("config.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Config;
+
+    fn error(source: &str) -> (usize, String) {
+        let err = Config::parse(source)
+            .err()
+            .expect("the config should be rejected");
+        (err.line, err.message)
+    }
+
+    #[test]
+    fn paddle_scales_must_be_above_0() {
+        for value in ["0", "-0.5", "nan", "inf"] {
+            let (line, message) = error(&format!("[paddle]\nmin_scale = {}", value));
+            assert_eq!(line, 2);
+            assert!(message.starts_with("`paddle.min_scale` must be above 0"));
+        }
+    }
+
+    #[test]
+    fn smallest_paddle_must_fit_the_largest() {
+        let (line, message) = error("[paddle]\nmin_scale = 0.5\nmax_scale = 0.4\nzone = 0.3");
+        assert_eq!(line, 3);
+        assert!(message.contains("must be at most `paddle.max_scale`"));
+
+        // Read in either order, only the end result counts
+        assert!(Config::parse("[paddle]\nmin_scale = 1.5\nmax_scale = 2.0")
+            .is_ok_and(|config| config.paddle_sizing.min_scale == 1.5));
+    }
+}
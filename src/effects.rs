@@ -0,0 +1,272 @@
+use std::{collections::VecDeque, str::FromStr};
+
+use rand::Rng;
+use sfml::{
+    graphics::Color,
+    system::{Vector2, Vector2f, Vector2u},
+};
+
+use crate::{
+    entity::Entity,
+    render::{Primitive, SceneWindow},
+    serve::Side,
+    utils,
+};
+
+/// How the ball shows its speed
+#[derive(Clone, Copy, PartialEq)]
+pub enum TrailStyle {
+    Off,
+    // Fading copies of the ball window where it's just been
+    Ghosts,
+    // Fading circles inside the ball window, longer the faster it goes
+    Streaks,
+}
+
+impl FromStr for TrailStyle {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "none" => Ok(TrailStyle::Off),
+            "ghosts" => Ok(TrailStyle::Ghosts),
+            "streaks" => Ok(TrailStyle::Streaks),
+            _ => Err(format!(
+                "unknown trail `{}` (expected none, ghosts or streaks)",
+                value
+            )),
+        }
+    }
+}
+
+/// Which effects are drawn, ghost windows cost a window each so they're off by default
+pub struct EffectSettings {
+    pub trail: TrailStyle,
+    pub trail_length: usize,
+    pub particles: bool,
+    pub edge_flash: bool,
+}
+
+const PARTICLE_LIFE: u32 = 30;
+const FLASH_FRAMES: u32 = 20;
+const FLASH_WIDTH: f32 = 40.0;
+
+struct Particle {
+    // Paddle window the particle is drawn in
+    paddle: String,
+    // Desktop coordinates
+    position: Vector2f,
+    velocity: Vector2f,
+    color: Color,
+    life: u32,
+}
+
+pub struct Effects<'a> {
+    settings: &'a EffectSettings,
+    desktop_size: Vector2u,
+    // Previous ball positions, newest first
+    trail: VecDeque<Vector2f>,
+    particles: Vec<Particle>,
+    flash: Option<(Side, u32)>,
+    flash_color: Color,
+}
+
+impl Effects<'_> {
+    pub fn new(
+        settings: &EffectSettings,
+        flash_color: Color,
+        desktop_size: Vector2u,
+    ) -> Effects<'_> {
+        Effects {
+            settings,
+            desktop_size,
+            trail: VecDeque::new(),
+            particles: Vec::new(),
+            flash: None,
+            flash_color,
+        }
+    }
+
+    /// Advances every effect by a frame
+    pub fn update(&mut self, ball: &Entity, playing: bool) {
+        if playing && self.settings.trail == TrailStyle::Ghosts {
+            self.trail.push_front(ball.position);
+            self.trail.truncate(self.settings.trail_length);
+        } else {
+            // The ball jumps to the server between points
+            self.trail.clear();
+        }
+
+        for particle in &mut self.particles {
+            particle.position += particle.velocity;
+            particle.velocity *= 0.9;
+            particle.life -= 1;
+        }
+        self.particles.retain(|particle| particle.life > 0);
+
+        if let Some((_, timer)) = &mut self.flash {
+            *timer -= 1;
+            if *timer == 0 {
+                self.flash = None;
+            }
+        }
+    }
+
+    /// Sprays particles into the paddle from where the ball hit it
+    pub fn on_hit(&mut self, paddle: &Entity, ball: &Entity, smash: bool) {
+        if !self.settings.particles {
+            return;
+        }
+
+        // Upright paddles are hit on their left or right, lying ones on their top or bottom
+        let upright = paddle.size.y >= paddle.size.x;
+        let facing = if upright {
+            (ball.position.x - paddle.position.x).signum()
+        } else {
+            (ball.position.y - paddle.position.y).signum()
+        };
+        let half_size = Vector2::new(paddle.half_size.x as f32, paddle.half_size.y as f32);
+        let contact = if upright {
+            Vector2::new(
+                paddle.position.x + half_size.x * facing,
+                ball.position.y.clamp(
+                    paddle.position.y - half_size.y,
+                    paddle.position.y + half_size.y,
+                ),
+            )
+        } else {
+            Vector2::new(
+                ball.position.x.clamp(
+                    paddle.position.x - half_size.x,
+                    paddle.position.x + half_size.x,
+                ),
+                paddle.position.y + half_size.y * facing,
+            )
+        };
+
+        let (count, speed) = if smash { (30, 12.0) } else { (12, 6.0) };
+
+        let mut rng = utils::rng();
+        for _ in 0..count {
+            // Spread around the direction pointing into the paddle
+            let angle: f32 = rng.gen_range(-70.0f32..70.0).to_radians();
+            let particle_speed = speed * rng.gen_range(0.5..1.0);
+            self.particles.push(Particle {
+                paddle: paddle.name.clone(),
+                position: contact,
+                velocity: if upright {
+                    Vector2::new(-facing * angle.cos(), angle.sin())
+                } else {
+                    Vector2::new(angle.sin(), -facing * angle.cos())
+                } * particle_speed,
+                color: ball.color,
+                life: rng.gen_range(PARTICLE_LIFE / 2..=PARTICLE_LIFE),
+            });
+        }
+    }
+
+    /// Flashes the edge of the screen behind whoever conceded
+    pub fn on_point(&mut self, conceded: Side) {
+        if self.settings.edge_flash {
+            self.flash = Some((conceded, FLASH_FRAMES));
+        }
+    }
+
+    /// Adds the ball streaks or the particles belonging in an entity's window
+    pub fn decorate(&self, window: &mut SceneWindow, ball: &Entity) {
+        let view_scale = Vector2::new(
+            window.view_size.x as f32 / window.size.x.max(1) as f32,
+            window.view_size.y as f32 / window.size.y.max(1) as f32,
+        );
+
+        if window.name == ball.name && self.settings.trail == TrailStyle::Streaks {
+            let speed = (ball.velocity.x.powi(2) + ball.velocity.y.powi(2)).sqrt();
+            if speed > 0.0 {
+                let center = Vector2::new(
+                    window.view_size.x as f32 / 2.0,
+                    window.view_size.y as f32 / 2.0,
+                );
+                let direction = ball.velocity / speed;
+                // One circle for every few pixels the ball covers in a frame
+                let count = (speed / 4.0) as usize;
+                for index in (1..=count).rev() {
+                    let fade = 1.0 - index as f32 / (count + 1) as f32;
+                    window.primitives.push(Primitive::Circle {
+                        center: center - direction * (index as f32 * 6.0),
+                        radius: center.x * 0.6 * fade,
+                        color: darken(ball.color, 0.75, (120.0 * fade) as u8),
+                    });
+                }
+            }
+        }
+
+        let origin = Vector2::new(window.position.x as f32, window.position.y as f32);
+        for particle in self.particles.iter().filter(|p| p.paddle == window.name) {
+            let local = particle.position - origin;
+            let fade = particle.life as f32 / PARTICLE_LIFE as f32;
+            window.primitives.push(Primitive::Circle {
+                center: Vector2::new(local.x * view_scale.x, local.y * view_scale.y),
+                radius: 3.0 + 3.0 * fade,
+                color: Color::rgba(
+                    particle.color.r,
+                    particle.color.g,
+                    particle.color.b,
+                    (255.0 * fade) as u8,
+                ),
+            });
+        }
+    }
+
+    /// Ghost windows and edge flashes, drawn behind everything else
+    pub fn windows(&self, ball: &Entity) -> Vec<SceneWindow> {
+        let mut windows = Vec::new();
+
+        let length = self.trail.len();
+        for (index, position) in self.trail.iter().enumerate().rev() {
+            let fade = 1.0 - (index + 1) as f32 / (length + 1) as f32;
+            let size = Vector2::new(
+                (ball.size.x as f32 * fade).max(1.0) as u32,
+                (ball.size.y as f32 * fade).max(1.0) as u32,
+            );
+            windows.push(SceneWindow {
+                name: format!("Trail {}", index),
+                position: Vector2::new(
+                    position.x as i32 - size.x as i32 / 2,
+                    position.y as i32 - size.y as i32 / 2,
+                ),
+                size,
+                view_size: size,
+                background: darken(ball.color, fade, 255),
+                primitives: Vec::new(),
+            });
+        }
+
+        if let Some((side, timer)) = self.flash {
+            let width = (FLASH_WIDTH * timer as f32 / FLASH_FRAMES as f32).max(1.0) as u32;
+            let x = match side {
+                Side::Player => 0,
+                Side::Ai => self.desktop_size.x.saturating_sub(width) as i32,
+            };
+            windows.push(SceneWindow {
+                name: String::from("Flash"),
+                position: Vector2::new(x, 0),
+                size: Vector2::new(width, self.desktop_size.y),
+                view_size: Vector2::new(width, self.desktop_size.y),
+                background: self.flash_color,
+                primitives: Vec::new(),
+            });
+        }
+
+        windows
+    }
+}
+
+/// Scales a color towards black
+fn darken(color: Color, amount: f32, alpha: u8) -> Color {
+    Color::rgba(
+        (color.r as f32 * amount) as u8,
+        (color.g as f32 * amount) as u8,
+        (color.b as f32 * amount) as u8,
+        alpha,
+    )
+}
@@ -1,16 +1,16 @@
 use rand::Rng;
-use sfml::{
-    graphics::{CircleShape, Color, RenderTarget, RenderWindow, Shape, Transformable},
-    system::Vector2,
-    window::{ContextSettings, Style},
-};
+use sfml::{graphics::Color, system::Vector2};
 
-use crate::utils::{interpolate_angle, normalize_vector};
+use crate::{
+    render::{Align, Primitive, SceneWindow},
+    theme::Theme,
+    utils::{self, interpolate_angle, normalize_vector},
+};
 
-pub struct Entity<'a> {
-    // Window
-    pub window: RenderWindow,
+// Gap between the middles of two life dots
+const LIFE_SPACING: f32 = 15.0;
 
+pub struct Entity {
     // Position
     pub position: Vector2<f32>,
     pub init_position: Vector2<f32>,
@@ -32,9 +32,28 @@ pub struct Entity<'a> {
 
     // Body
     pub color: Color,
+    // How charged the paddle's shot is, from 0 to 1
+    pub charge: f32,
+    // How big the paddle is compared to its size limits, from 0 to 1
+    pub size_meter: Option<f32>,
+    // Text written across the middle of the window
+    pub text: Option<String>,
+    pub text_size: u32,
+    pub text_align: Align,
+    // Gap to the window edge for left and right aligned text
+    pub text_padding: f32,
+    // Lives left for each side, drawn as dots along the bottom in the side's color
+    pub lives: Vec<(u8, Color)>,
+    // Colors for the eye and text
+    pub theme: Theme,
+    // Keeps the window on the entity through impacts, so a player dragging it isn't fought
+    pub steady: bool,
     // Eye
-    _eye_white: CircleShape<'a>,
-    _eye_pupil: CircleShape<'a>,
+    pub has_eye: bool,
+    _eye_position: Vector2<f32>,
+    _eye_radius: f32,
+    _pupil_radius: f32,
+    _pupil_rotation: f32,
     _look_score_timer: i32,
     _max_look_score_timer: i32,
     _look_score_countdown: i32,
@@ -56,7 +75,7 @@ pub struct Entity<'a> {
     _display_scale: f32,
 }
 
-impl Entity<'_> {
+impl Entity {
     pub fn new(
         position: Vector2<f32>,
         width: u32,
@@ -65,37 +84,11 @@ impl Entity<'_> {
         color: Color,
         spring_stiffness: f32,
         spring_damping: f32,
-    ) -> Entity<'static> {
-        let mut window = RenderWindow::new(
-            (width, height),
-            "",
-            Style::CLOSE,
-            &ContextSettings::default(),
-        );
-
+    ) -> Entity {
         let size: Vector2<u32> = Vector2::new(width, height);
         let half_size: Vector2<u32> = Vector2::new(width / 2, height / 2);
 
-        window.set_position(Vector2::new(
-            position.x as i32 - half_size.x as i32,
-            position.y as i32 - half_size.y as i32,
-        ));
-
-        // Eye
-
-        // Eye white
-        let mut eye_white = CircleShape::new(20.0, 30);
-        eye_white.set_fill_color(Color::WHITE);
-        eye_white.set_origin(Vector2::new(20.0, 20.0));
-        eye_white.set_position(Vector2::new(half_size.x as f32, 35.0));
-
-        // Eye pupil
-        let mut eye_pupil = CircleShape::new(7.5, 30);
-        eye_pupil.set_fill_color(Color::BLACK);
-        eye_pupil.set_origin(Vector2::new(-2.5, 7.5));
-        eye_pupil.set_position(eye_white.position());
-
-        let mut rng = rand::thread_rng();
+        let mut rng = utils::rng();
 
         const MAX_LOOK_SCORE_COUNTDOWN: i32 = 600;
         const MIN_LOOK_SCORE_COUNTDOWN: i32 = 300;
@@ -104,7 +97,6 @@ impl Entity<'_> {
         const MIN_BLINK_COUNTDOWN: i32 = 180;
 
         Entity {
-            window,
             position,
             init_position: position,
             prev_position: position,
@@ -116,9 +108,21 @@ impl Entity<'_> {
             scale: Vector2::new(1.0, 1.0),
             name,
             color,
+            charge: 0.0,
+            size_meter: None,
+            text: None,
+            text_size: 75,
+            text_align: Align::Center,
+            text_padding: 20.0,
+            lives: Vec::new(),
+            theme: Theme::default(),
+            steady: false,
             // Eye
-            _eye_white: eye_white,
-            _eye_pupil: eye_pupil,
+            has_eye: true,
+            _eye_position: Vector2::new(half_size.x as f32, 35.0),
+            _eye_radius: 20.0,
+            _pupil_radius: 7.5,
+            _pupil_rotation: 0.0,
             _look_score_timer: 0,
             _max_look_score_timer: 25,
             _look_score_countdown: rng
@@ -145,37 +149,20 @@ impl Entity<'_> {
         self.prev_position = self.position;
 
         self.position += self.velocity;
-
-        self.window.set_position(Vector2::new(
-            self.position.x as i32 - self.half_size.x as i32 + self._offset.x as i32,
-            self.position.y as i32 - self.half_size.y as i32 + self._offset.y as i32,
-        ));
     }
 
     pub fn move_offset(&mut self) {
         self._offset += self._offset_velocity;
-
-        self.window.set_position(Vector2::new(
-            self.position.x as i32 - self.half_size.x as i32 + self._offset.x as i32,
-            self.position.y as i32 - self.half_size.y as i32 + self._offset.y as i32,
-        ));
     }
 
     pub fn set_position(&mut self, pos_x: Option<f32>, pos_y: Option<f32>) {
-        match pos_x {
-            Some(x) => self.position.x = x,
-            None => (),
+        if let Some(x) = pos_x {
+            self.position.x = x;
         }
 
-        match pos_y {
-            Some(y) => self.position.y = y,
-            None => (),
+        if let Some(y) = pos_y {
+            self.position.y = y;
         }
-
-        self.window.set_position(Vector2::new(
-            self.position.x as i32 - self.half_size.x as i32 + self._offset.x as i32,
-            self.position.y as i32 - self.half_size.y as i32 + self._offset.y as i32,
-        ));
     }
 
     pub fn set_scale(&mut self, scale: f32) {
@@ -192,39 +179,30 @@ impl Entity<'_> {
         );
 
         self.half_size = Vector2::new(self.size.x / 2, self.size.y / 2);
+    }
 
-        self.window.set_size(self.size * self._display_scale as u32);
-
-        self.window.set_position(Vector2::new(
-            self.position.x as i32 - (self.half_size.x as i32 * self._display_scale as i32)
-                + self._offset.x as i32,
-            self.position.y as i32 - (self.half_size.y as i32 * self._display_scale as i32)
-                + self._offset.y as i32,
-        ));
+    /// Changes the unscaled size of the window, keeping it centered on the same spot
+    pub fn set_size(&mut self, width: u32, height: u32) {
+        self.init_size = Vector2::new(width, height);
+        self.size = Vector2::new(
+            (width as f32 * self.scale.x) as u32,
+            (height as f32 * self.scale.y) as u32,
+        );
+        self.half_size = Vector2::new(self.size.x / 2, self.size.y / 2);
+        self._eye_position.x = (width / 2) as f32;
     }
 
     pub fn set_display_scale(&mut self, scale: f32) {
         self._display_scale = scale;
-
-        self.window.set_size(self.size * self._display_scale as u32);
-
-        self.window.set_position(Vector2::new(
-            self.position.x as i32 - (self.half_size.x as i32 * self._display_scale as i32)
-                + self._offset.x as i32,
-            self.position.y as i32 - (self.half_size.y as i32 * self._display_scale as i32)
-                + self._offset.y as i32,
-        ));
     }
 
     pub fn set_scale_xy(&mut self, scale_x: Option<f32>, scale_y: Option<f32>) {
-        match scale_x {
-            Some(x) => self.scale.x = x,
-            None => (),
+        if let Some(x) = scale_x {
+            self.scale.x = x;
         }
 
-        match scale_y {
-            Some(y) => self.scale.y = y,
-            None => (),
+        if let Some(y) = scale_y {
+            self.scale.y = y;
         }
 
         self.size = Vector2::new(
@@ -233,15 +211,6 @@ impl Entity<'_> {
         );
 
         self.half_size = Vector2::new(self.size.x / 2, self.size.y / 2);
-
-        self.window.set_size(self.size * self._display_scale as u32);
-
-        self.window.set_position(Vector2::new(
-            self.position.x as i32 - (self.half_size.x as i32 * self._display_scale as i32)
-                + self._offset.x as i32,
-            self.position.y as i32 - (self.half_size.y as i32 * self._display_scale as i32)
-                + self._offset.y as i32,
-        ));
     }
 
     pub fn get_overlap(entity_1: &Entity, entity_2: &Entity) -> Vector2<i32> {
@@ -255,7 +224,7 @@ impl Entity<'_> {
             (entity_1.half_size.y + entity_2.half_size.y) as i32 - delta.y,
         );
 
-        return overlap;
+        overlap
     }
 
     pub fn get_prev_overlap(entity_1: &Entity, entity_2: &Entity) -> Vector2<i32> {
@@ -269,59 +238,148 @@ impl Entity<'_> {
             (entity_1.half_size.y + entity_2.half_size.y) as i32 - delta.y,
         );
 
-        return overlap;
+        overlap
     }
 
-    pub fn draw(&mut self, score_board: &Entity, ball: &Entity) {
-        self.window.clear(self.color);
+    fn eye_visible(&self) -> bool {
+        // Paddles close their eye while flashing the ball's color on a return
+        self.has_eye && self.color != self.theme.ball && self._blink_timer <= 0
+    }
 
-        if self.color != Color::WHITE && self._blink_timer <= 0 {
-            self.window.draw(&mut self._eye_white);
+    pub fn update_eye(&mut self, score_board: &Entity, ball: &Entity) {
+        if !self.eye_visible() {
+            return;
+        }
 
-            let player_look_target = if self._look_score_timer > 0 {
-                score_board.position
-            } else {
-                ball.position
-            };
+        let player_look_target = if self._look_score_timer > 0 {
+            score_board.position
+        } else {
+            ball.position
+        };
 
-            let player_look_dir = normalize_vector(
-                player_look_target
-                    - (self.position + self._eye_white.position()
-                        - Vector2::new(self.half_size.x as f32, self.half_size.y as f32)),
-            );
-
-            let player_look_angle: f32 = player_look_dir.y.atan2(player_look_dir.x).to_degrees();
-
-            if self._following_target {
-                // Have the eye track the target
-                self._eye_pupil.set_rotation(player_look_angle);
-            } else {
-                // Have the eye rotate to the targets position
-                self._eye_pupil.set_rotation(interpolate_angle(
-                    self._eye_pupil.rotation(),
-                    player_look_angle,
-                    0.2,
-                ));
-                if (self._eye_pupil.rotation() - player_look_angle).abs() < 10.0 {
-                    self._following_target = true;
-                }
-            }
+        let player_look_dir = normalize_vector(
+            player_look_target
+                - (self.position + self._eye_position
+                    - Vector2::new(self.half_size.x as f32, self.half_size.y as f32)),
+        );
 
-            if self.color == Color::YELLOW {
-                self._eye_pupil.set_fill_color(Color::GREEN);
+        let player_look_angle: f32 = player_look_dir.y.atan2(player_look_dir.x).to_degrees();
+
+        if self._following_target {
+            // Have the eye track the target
+            self._pupil_rotation = player_look_angle;
+        } else {
+            // Have the eye rotate to the targets position
+            self._pupil_rotation = interpolate_angle(self._pupil_rotation, player_look_angle, 0.2);
+            if (self._pupil_rotation - player_look_angle).abs() < 10.0 {
+                self._following_target = true;
             }
+        }
+
+        // Pupil dilates as a shot charges
+        self._pupil_radius = 7.5 * (1.0 + self.charge * 0.3);
+    }
 
-            self.window.draw(&mut self._eye_pupil);
-            self._eye_pupil.set_fill_color(Color::BLACK);
+    /// The entity's window as it should look this frame
+    pub fn scene_window(&self) -> SceneWindow {
+        let view_size = Vector2::new(self.init_size.x as f32, self.init_size.y as f32);
+        let mut primitives = Vec::new();
+
+        // Charge meter, filling up from the bottom of the paddle
+        if self.charge > 0.0 {
+            let fill_height = view_size.y * self.charge;
+            primitives.push(Primitive::Rect {
+                position: Vector2::new(0.0, view_size.y - fill_height),
+                size: Vector2::new(view_size.x, fill_height),
+                color: self.theme.charge,
+            });
         }
 
-        self.window.display();
+        // Size meter along the bottom of the paddle
+        if let Some(size_meter) = self.size_meter {
+            let margin = 8.0;
+            primitives.push(Primitive::Rect {
+                position: Vector2::new(margin, view_size.y - margin - 6.0),
+                size: Vector2::new((view_size.x - margin * 2.0) * size_meter, 6.0),
+                color: self.theme.size_meter,
+            });
+        }
+
+        if self.eye_visible() {
+            primitives.push(Primitive::Circle {
+                center: self._eye_position,
+                radius: self._eye_radius,
+                color: self.theme.eye,
+            });
+
+            // The pupil sits against the edge of the eye white, in the direction it's looking
+            let pupil_angle = self._pupil_rotation.to_radians();
+            let pupil_distance = self._eye_radius - 2.5 - self._pupil_radius;
+            primitives.push(Primitive::Circle {
+                center: self._eye_position
+                    + Vector2::new(pupil_angle.cos(), pupil_angle.sin()) * pupil_distance,
+                radius: self._pupil_radius,
+                color: if self.color == self.theme.smash {
+                    self.theme.smash_pupil
+                } else {
+                    self.theme.pupil
+                },
+            });
+        }
 
-        self.set_display_scale(1.0);
+        if let Some(text) = &self.text {
+            let x = match self.text_align {
+                Align::Left => self.text_padding,
+                Align::Center => view_size.x / 2.0,
+                Align::Right => view_size.x - self.text_padding,
+            };
+            primitives.push(Primitive::Text {
+                string: text.clone(),
+                position: Vector2::new(x, view_size.y / 2.0),
+                size: self.text_size,
+                align: self.text_align,
+                color: self.theme.score_text,
+            });
+        }
+
+        // One group of dots per side, spread across the window
+        for (index, &(lives, color)) in self.lives.iter().enumerate() {
+            let group_center = view_size.x * (index as f32 + 0.5) / self.lives.len() as f32;
+            let first = group_center - (lives as f32 - 1.0) * LIFE_SPACING / 2.0;
+            for life in 0..lives {
+                primitives.push(Primitive::Circle {
+                    center: Vector2::new(
+                        first + life as f32 * LIFE_SPACING,
+                        view_size.y - LIFE_SPACING,
+                    ),
+                    radius: LIFE_SPACING / 3.0,
+                    color,
+                });
+            }
+        }
+
+        let (offset, display_scale) = if self.steady {
+            (Vector2::new(0.0, 0.0), 1.0)
+        } else {
+            (self._offset, self._display_scale)
+        };
+        SceneWindow {
+            name: self.name.clone(),
+            position: Vector2::new(
+                self.position.x as i32 - (self.half_size.x as i32 * display_scale as i32)
+                    + offset.x as i32,
+                self.position.y as i32 - (self.half_size.y as i32 * display_scale as i32)
+                    + offset.y as i32,
+            ),
+            size: self.size * display_scale as u32,
+            view_size: self.init_size,
+            background: self.color,
+            primitives,
+        }
     }
 
     pub fn update_eye_timers(&mut self) {
-        let mut rng = rand::thread_rng();
+        let mut rng = utils::rng();
 
         //  Player look score
 
@@ -385,3 +443,152 @@ impl Entity<'_> {
         self.move_offset();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use sfml::{graphics::Color, system::Vector2};
+
+    use super::Entity;
+    use crate::{
+        render::Scene,
+        snapshot::{assert_snapshot, render},
+        theme::Theme,
+    };
+
+    const PADDLE_WIDTH: u32 = 75;
+    const PADDLE_HEIGHT: u32 = 300;
+
+    /// A paddle whose window fills the whole snapshot
+    fn paddle(color: Color) -> Entity {
+        let mut paddle = Entity::new(
+            Vector2::new((PADDLE_WIDTH / 2) as f32, (PADDLE_HEIGHT / 2) as f32),
+            PADDLE_WIDTH,
+            PADDLE_HEIGHT,
+            String::from("Player"),
+            color,
+            0.75,
+            0.75,
+        );
+        paddle._following_target = true;
+        paddle
+    }
+
+    fn target(name: &str, x: f32, y: f32) -> Entity {
+        Entity::new(
+            Vector2::new(x, y),
+            100,
+            100,
+            String::from(name),
+            Color::WHITE,
+            1.0,
+            1.0,
+        )
+    }
+
+    fn assert_paddle_snapshot(name: &str, paddle: &mut Entity) {
+        let score_board = target("Score", 300.0, -200.0);
+        let ball = target("Ball", 600.0, 450.0);
+        paddle.update_eye(&score_board, &ball);
+
+        let scene = Scene {
+            windows: vec![paddle.scene_window()],
+        };
+        let size = Vector2::new(PADDLE_WIDTH, PADDLE_HEIGHT);
+        assert_snapshot(name, &render(&scene, size, size));
+    }
+
+    #[test]
+    fn snapshot_paddle_looking_at_ball() {
+        let mut paddle = paddle(Color::BLUE);
+        assert_paddle_snapshot("paddle_looking_at_ball", &mut paddle);
+    }
+
+    #[test]
+    fn snapshot_paddle_looking_at_score_board() {
+        let mut paddle = paddle(Color::BLUE);
+        paddle._look_score_timer = paddle._max_look_score_timer;
+        assert_paddle_snapshot("paddle_looking_at_score_board", &mut paddle);
+    }
+
+    #[test]
+    fn snapshot_paddle_blinking() {
+        let mut paddle = paddle(Color::RED);
+        paddle._blink_timer = paddle._max_blink_timer;
+        assert_paddle_snapshot("paddle_blinking", &mut paddle);
+    }
+
+    #[test]
+    fn snapshot_paddle_smashing() {
+        let mut paddle = paddle(Color::YELLOW);
+        assert_paddle_snapshot("paddle_smashing", &mut paddle);
+    }
+
+    #[test]
+    fn snapshot_paddle_charging() {
+        let mut paddle = paddle(Color::BLUE);
+        paddle.charge = 0.5;
+        paddle.size_meter = Some(0.75);
+        assert_paddle_snapshot("paddle_charging", &mut paddle);
+    }
+
+    #[test]
+    fn snapshot_paddle_colorblind_theme() {
+        let theme: Theme = "colorblind".parse().unwrap();
+        let mut paddle = paddle(theme.smash);
+        paddle.theme = theme;
+        assert_paddle_snapshot("paddle_colorblind_theme", &mut paddle);
+    }
+
+    #[test]
+    fn snapshot_score_board_lives() {
+        let mut score_board = target("Score", 125.0, 50.0);
+        score_board.set_size(250, 100);
+        score_board.has_eye = false;
+        score_board.color = Color::WHITE;
+        score_board.lives = vec![(3, Color::BLUE), (0, Color::GREEN), (1, Color::RED)];
+
+        let scene = Scene {
+            windows: vec![score_board.scene_window()],
+        };
+        let size = Vector2::new(250, 100);
+        assert_snapshot("score_board_lives", &render(&scene, size, size));
+    }
+
+    #[test]
+    fn snapshot_desktop() {
+        // As wide as auto resizing makes it for the names
+        let mut score_board = Entity::new(
+            Vector2::new(960.0, 150.0),
+            760,
+            100,
+            String::from("Score"),
+            Color::WHITE,
+            0.5,
+            0.85,
+        );
+        score_board.text = Some(String::from("Player 3 - 2 AI"));
+
+        let mut ball = target("Ball", 700.0, 400.0);
+        ball.has_eye = false;
+
+        let mut player = paddle(Color::BLUE);
+        player.set_position(Some(150.0), Some(540.0));
+        let mut ai = paddle(Color::RED);
+        ai.set_position(Some(1770.0), Some(540.0));
+        ai.set_scale_xy(None, Some(0.5));
+
+        player.update_eye(&score_board, &ball);
+        ai.update_eye(&score_board, &ball);
+
+        let scene = Scene {
+            windows: vec![
+                score_board.scene_window(),
+                player.scene_window(),
+                ai.scene_window(),
+                ball.scene_window(),
+            ],
+        };
+        let frame = render(&scene, Vector2::new(1920, 1080), Vector2::new(480, 270));
+        assert_snapshot("desktop", &frame);
+    }
+}
@@ -0,0 +1,44 @@
+use std::fmt;
+
+/// Why the game couldn't get going or had to stop, each kind with its own exit code
+pub enum GameError {
+    // Nowhere to open windows, like an SSH session or a headless machine
+    NoDisplay,
+    // Name of the window the window manager wouldn't open
+    Window(String),
+    // Which font, like "the built-in font"
+    Font(String),
+    Config(String),
+    // A replay, profile or capture that couldn't be read or written
+    File(String),
+}
+
+impl GameError {
+    /// 2 is left for command line mistakes
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            GameError::File(_) => 1,
+            GameError::Config(_) => 3,
+            GameError::Font(_) => 4,
+            GameError::NoDisplay => 5,
+            GameError::Window(_) => 6,
+        }
+    }
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::NoDisplay => write!(
+                f,
+                "No display to open the game windows on, is DISPLAY set? \
+                 `simulate` and `replay --export` work without one"
+            ),
+            GameError::Window(name) => write!(f, "Couldn't open the {} window", name),
+            GameError::Font(font) => write!(f, "Couldn't load {}", font),
+            GameError::Config(message) | GameError::File(message) => {
+                write!(f, "{}", message)
+            }
+        }
+    }
+}
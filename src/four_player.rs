@@ -0,0 +1,620 @@
+//! Four paddles, one on each edge of the arena
+
+use std::str::FromStr;
+
+use rand::Rng;
+use sfml::{
+    graphics::{Color, Font},
+    system::{Vector2, Vector2f},
+    window::{Event, Key},
+};
+
+use crate::{
+    config::Config,
+    effects::Effects,
+    entity::Entity,
+    error::GameError,
+    game::{accelerate, bounce_off, scene, set_score_text, GameOptions},
+    input::{self, Control},
+    render::{Renderer, Scene},
+    session::Session,
+    theme::Theme,
+    utils,
+};
+
+/// An edge of the arena, guarded by a paddle or walled off
+#[derive(Clone, Copy, PartialEq)]
+pub enum Edge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl Edge {
+    // In the same order as `Config::seats`, and as `as usize` counts them
+    pub const ALL: [Edge; 4] = [Edge::Left, Edge::Right, Edge::Top, Edge::Bottom];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Edge::Left => "Left",
+            Edge::Right => "Right",
+            Edge::Top => "Top",
+            Edge::Bottom => "Bottom",
+        }
+    }
+
+    /// Left and right paddles stand up and move up and down, the others lie along their edge
+    fn upright(self) -> bool {
+        matches!(self, Edge::Left | Edge::Right)
+    }
+
+    /// The part of a vector along the edge, the way the paddle moves
+    fn along(self, vector: Vector2f) -> f32 {
+        if self.upright() {
+            vector.y
+        } else {
+            vector.x
+        }
+    }
+
+    /// Points out of the arena through the edge
+    fn outward(self) -> Vector2f {
+        match self {
+            Edge::Left => Vector2::new(-1.0, 0.0),
+            Edge::Right => Vector2::new(1.0, 0.0),
+            Edge::Top => Vector2::new(0.0, -1.0),
+            Edge::Bottom => Vector2::new(0.0, 1.0),
+        }
+    }
+
+    /// Keys that move the paddle back and forth when several people share the keyboard
+    fn keys(self) -> (Key, Key) {
+        match self {
+            Edge::Left => (Key::W, Key::S),
+            Edge::Right => (Key::Up, Key::Down),
+            Edge::Top => (Key::C, Key::V),
+            Edge::Bottom => (Key::K, Key::L),
+        }
+    }
+}
+
+/// Who plays an edge
+#[derive(Clone, Copy, PartialEq)]
+pub enum Seat {
+    Human,
+    Ai,
+    // Nobody, the edge is a wall
+    Empty,
+}
+
+impl FromStr for Seat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "human" => Ok(Seat::Human),
+            "ai" => Ok(Seat::Ai),
+            "none" => Ok(Seat::Empty),
+            _ => Err(format!(
+                "unknown seat `{}` (expected human, ai or none)",
+                value
+            )),
+        }
+    }
+}
+
+pub struct FourPlayerResult {
+    pub winner: Edge,
+    // Points for each edge, or lives left when playing with lives, in `Edge::ALL` order
+    pub scores: [u8; 4],
+}
+
+const PADDLE_WIDTH: u32 = 75;
+const PADDLE_LENGTH: u32 = 300;
+// The arena starts below the top of the desktop, the same as in classic
+const TOP: f32 = 48.0;
+
+struct Paddle {
+    edge: Edge,
+    human: bool,
+    entity: Entity,
+    // Where it starts and goes back to after each point
+    home: Vector2f,
+}
+
+/// Plays with a paddle on every seated edge until the windows are closed or enough matches
+/// are over. The last paddle to touch the ball scores when it leaves through another edge, or
+/// with lives the edge it left through loses one
+pub fn run(
+    config: &Config,
+    renderer: &mut dyn Renderer,
+    font: Option<&Font>,
+    options: &GameOptions,
+) -> Result<Vec<FourPlayerResult>, GameError> {
+    const MAX_PADDLE_SPEED: f32 = 15.0;
+    const INIT_BALL_SPEED: f32 = 20.0;
+    let mut max_ball_speed: f32 = INIT_BALL_SPEED;
+
+    const BALL_SIDE: u32 = 100;
+    const SCORE_BOARD_WIDTH: u32 = 250;
+    const SCORE_BOARD_HEIGHT: u32 = 100;
+
+    // Furthest a serve goes from straight at an edge, in degrees
+    const MAX_SERVE_ANGLE: f32 = 30.0;
+
+    // Everyone shares the keyboard, and the paddles stay on their edges
+    if config.control != Control::Keyboard {
+        return Err(GameError::Config(String::from(
+            "Four player mode is played on the keyboard, set `input.control` to `keyboard`",
+        )));
+    }
+    if config.free_movement {
+        return Err(GameError::Config(String::from(
+            "Paddles stay on their edge in four player mode, turn `paddle.free_movement` off",
+        )));
+    }
+    // With no smashes, the difficulty is how fast the AI paddles get around
+    let ai_speed = MAX_PADDLE_SPEED * config.ai_difficulty.paddle_speed();
+
+    let starting_seats = config.seats.map(|seat| match seat {
+        Seat::Human if options.autopilot => Seat::Ai,
+        seat => seat,
+    });
+    if starting_seats
+        .iter()
+        .filter(|&&seat| seat != Seat::Empty)
+        .count()
+        < 2
+    {
+        return Err(GameError::Config(String::from(
+            "Four player mode needs at least two edges that aren't `none`",
+        )));
+    }
+    // Someone playing alone uses the arrow keys wherever they sit
+    let lone_human = starting_seats
+        .iter()
+        .filter(|&&seat| seat == Seat::Human)
+        .count()
+        == 1;
+
+    let theme = config.theme;
+    let arena = Vector2::new(options.arena.x as f32, options.arena.y as f32);
+    let center = Vector2::new(arena.x / 2.0, arena.y / 2.0);
+
+    let mut ball = Entity::new(
+        center,
+        BALL_SIDE,
+        BALL_SIDE,
+        String::from("Ball"),
+        theme.ball,
+        1.0,
+        1.0,
+    );
+    ball.has_eye = false;
+
+    // The score board sits in the middle with the ball flying over it
+    let score_layout = &config.score_board_layout;
+    let mut score_board = Entity::new(
+        center,
+        SCORE_BOARD_WIDTH,
+        SCORE_BOARD_HEIGHT,
+        String::from("Score"),
+        theme.score_board,
+        0.5,
+        0.85,
+    );
+    score_board.has_eye = false;
+    score_board.text_size = score_layout.text_size / 2;
+    score_board.text_align = score_layout.align;
+    score_board.text_padding = score_layout.padding as f32;
+
+    // Edges become walls as their paddles are knocked out
+    let mut seats = starting_seats;
+    let mut paddles = seat_paddles(&seats, arena, theme);
+
+    ball.theme = theme;
+    score_board.theme = theme;
+
+    let mut effects = Effects::new(&config.effects, config.theme.flash, options.arena);
+    // Points, or lives left when playing with lives
+    let starting_scores = [config.lives; 4];
+    let mut scores = starting_scores;
+    let mut last_hit: Option<Edge> = None;
+
+    renderer.open(&frame_scene(&effects, &score_board, &paddles, &ball))?;
+
+    let mut session = Session::new(config, options.realtime);
+    let mut delay_multiplier: u32 = 1;
+
+    let mut playing = false;
+    let mut serve_timer = config.serve_ai_delay;
+    let mut results = Vec::new();
+    let mut held: Vec<Key> = Vec::new();
+
+    while session.running {
+        for evt in session.poll_events(renderer) {
+            match evt {
+                Event::KeyPressed { code, .. } if !held.contains(&code) => held.push(code),
+                Event::KeyReleased { code, .. } => held.retain(|&key| key != code),
+                _ => {}
+            }
+        }
+
+        if session.is_paused() {
+            set_score_text(
+                &mut score_board,
+                score_layout,
+                font,
+                format!("{}\nPaused", score_text(&paddles, &scores)),
+                Vector2::new(SCORE_BOARD_WIDTH, SCORE_BOARD_HEIGHT),
+            );
+            renderer.render(&frame_scene(&effects, &score_board, &paddles, &ball));
+
+            session.wait(1);
+            continue;
+        }
+
+        // Paddle logic
+
+        for paddle in &mut paddles {
+            let edge = paddle.edge;
+            let entity = &mut paddle.entity;
+
+            let (input, max_speed) = if paddle.human {
+                let (back, forth) = match (lone_human, edge.upright()) {
+                    (true, true) => (Key::Up, Key::Down),
+                    (true, false) => (Key::Left, Key::Right),
+                    (false, _) => edge.keys(),
+                };
+                let input = held.contains(&forth) as i8 - held.contains(&back) as i8;
+                (input, MAX_PADDLE_SPEED)
+            } else {
+                // Follow the ball while it's coming this way, otherwise drift back home
+                let coming = playing
+                    && edge.outward().x * ball.velocity.x + edge.outward().y * ball.velocity.y
+                        > 0.0;
+                let target = if coming {
+                    edge.along(ball.position)
+                } else {
+                    edge.along(paddle.home)
+                };
+                (input::follow(edge.along(entity.position), target), ai_speed)
+            };
+
+            if edge.upright() {
+                accelerate(
+                    &mut entity.velocity.y,
+                    &mut entity.acceleration.y,
+                    input,
+                    max_speed,
+                );
+            } else {
+                accelerate(
+                    &mut entity.velocity.x,
+                    &mut entity.acceleration.x,
+                    input,
+                    max_speed,
+                );
+            }
+
+            entity.r#move();
+
+            // Paddles stop at the ends of their edge
+            let half_size = Vector2::new(entity.half_size.x as f32, entity.half_size.y as f32);
+            if edge.upright() {
+                let clamped = entity
+                    .position
+                    .y
+                    .clamp(TOP + half_size.y, arena.y - half_size.y);
+                if clamped != entity.position.y {
+                    entity.velocity.y = 0.0;
+                    entity.set_position(None, Some(clamped));
+                }
+            } else {
+                let clamped = entity.position.x.clamp(half_size.x, arena.x - half_size.x);
+                if clamped != entity.position.x {
+                    entity.velocity.x = 0.0;
+                    entity.set_position(Some(clamped), None);
+                }
+            }
+        }
+
+        // Serve logic, from the middle towards a random seated edge
+
+        if !playing {
+            ball.set_position(Some(center.x), Some(center.y));
+            if serve_timer > 0 {
+                serve_timer -= 1;
+            } else {
+                let mut rng = utils::rng();
+                let target = paddles[rng.gen_range(0..paddles.len())].edge.outward();
+                let angle = rng
+                    .gen_range(-MAX_SERVE_ANGLE..MAX_SERVE_ANGLE)
+                    .to_radians();
+                ball.velocity = Vector2::new(
+                    target.x * angle.cos() - target.y * angle.sin(),
+                    target.x * angle.sin() + target.y * angle.cos(),
+                ) * INIT_BALL_SPEED;
+                last_hit = None;
+                playing = true;
+                if options.log_events {
+                    println!("Starting the game!");
+                }
+            }
+        }
+
+        // Ball logic
+
+        if playing {
+            ball.r#move();
+
+            for paddle in &mut paddles {
+                let overlap = Entity::get_overlap(&ball, &paddle.entity);
+                if overlap.x > 0 && overlap.y > 0 {
+                    if options.log_events {
+                        println!("Ball collided with {0}!", paddle.entity.name);
+                    }
+                    bounce_off(&mut ball, &paddle.entity, overlap, max_ball_speed);
+                    last_hit = Some(paddle.edge);
+
+                    effects.on_hit(&paddle.entity, &ball, false);
+                    paddle.entity.color = ball.color;
+                    paddle.entity.impact(&ball.velocity);
+                    delay_multiplier = 3;
+                }
+            }
+
+            let half_size = Vector2::new(ball.half_size.x as f32, ball.half_size.y as f32);
+            let out = Edge::ALL
+                .into_iter()
+                .zip(seats)
+                .find(|&(edge, _)| match edge {
+                    Edge::Left => ball.position.x - half_size.x < 0.0,
+                    Edge::Right => ball.position.x + half_size.x > arena.x,
+                    Edge::Top => ball.position.y - half_size.y < TOP,
+                    Edge::Bottom => ball.position.y + half_size.y > arena.y,
+                });
+
+            match out {
+                // Edges without a paddle are walls
+                Some((edge, Seat::Empty)) => {
+                    if options.log_events {
+                        println!("Ball collided with the {} wall!", edge.name());
+                    }
+                    if edge.upright() {
+                        ball.velocity.x = -edge.outward().x * ball.velocity.x.abs();
+                        ball.set_position(
+                            Some(ball.position.x.clamp(half_size.x, arena.x - half_size.x)),
+                            None,
+                        );
+                    } else {
+                        ball.velocity.y = -edge.outward().y * ball.velocity.y.abs();
+                        ball.set_position(
+                            None,
+                            Some(
+                                ball.position
+                                    .y
+                                    .clamp(TOP + half_size.y, arena.y - half_size.y),
+                            ),
+                        );
+                    }
+                }
+                Some((conceded, _)) => {
+                    let winner = if config.lives > 0 {
+                        // Whoever let it through loses a life, and their edge is walled off
+                        // once they're all gone
+                        let lives = &mut scores[conceded as usize];
+                        *lives = lives.saturating_sub(1);
+                        if *lives == 0 {
+                            if options.log_events {
+                                println!("{} is out!", conceded.name());
+                            }
+                            seats[conceded as usize] = Seat::Empty;
+                            paddles.retain(|paddle| paddle.edge != conceded);
+                            // Keys go to whoever's still in once the window that had them closes
+                            renderer.set_input_window(input_window(&seats));
+                        } else if options.log_events {
+                            println!("{} loses a life!", conceded.name());
+                        }
+                        match paddles.as_slice() {
+                            [last] => Some(last.edge),
+                            _ => None,
+                        }
+                    } else {
+                        // Nobody scores from an own goal or a serve nobody touched
+                        let scorer = last_hit.filter(|&edge| edge != conceded);
+                        if options.log_events {
+                            match scorer {
+                                Some(edge) => println!("Point to {}!", edge.name()),
+                                None => println!("Nobody scores!"),
+                            }
+                        }
+                        if let Some(scorer) = scorer {
+                            scores[scorer as usize] += 1;
+                        }
+                        scorer.filter(|&scorer| {
+                            config.points_to_win > 0
+                                && scores[scorer as usize] >= config.points_to_win
+                        })
+                    };
+
+                    if let Some(winner) = winner {
+                        if options.log_events {
+                            println!(
+                                "{} wins! {}",
+                                winner.name(),
+                                score_text(&paddles, &scores).replace('\n', ", ")
+                            );
+                        }
+                        results.push(FourPlayerResult { winner, scores });
+                        if options
+                            .matches
+                            .is_some_and(|matches| results.len() as u32 >= matches)
+                        {
+                            session.running = false;
+                        }
+                        scores = starting_scores;
+                        // Everyone knocked out comes back for the next match
+                        if config.lives > 0 {
+                            seats = starting_seats;
+                            paddles = seat_paddles(&seats, arena, theme);
+                            renderer.set_input_window(input_window(&seats));
+                        }
+                    }
+
+                    for paddle in &mut paddles {
+                        paddle.entity.velocity = Vector2::new(0.0, 0.0);
+                        paddle
+                            .entity
+                            .set_position(Some(paddle.home.x), Some(paddle.home.y));
+                    }
+
+                    playing = false;
+                    serve_timer = config.serve_ai_delay;
+                    ball.velocity = Vector2::new(0.0, 0.0);
+                    max_ball_speed = INIT_BALL_SPEED;
+                    delay_multiplier = 30;
+                }
+                None => {}
+            }
+        }
+
+        // Display
+
+        for paddle in &mut paddles {
+            paddle.entity.update_impact();
+            paddle.entity.update_eye(&score_board, &ball);
+        }
+
+        set_score_text(
+            &mut score_board,
+            score_layout,
+            font,
+            score_text(&paddles, &scores),
+            Vector2::new(SCORE_BOARD_WIDTH, SCORE_BOARD_HEIGHT),
+        );
+        if config.lives > 0 {
+            score_board.lives = paddles
+                .iter()
+                .map(|paddle| {
+                    (
+                        scores[paddle.edge as usize],
+                        paddle_color(paddle.human, theme),
+                    )
+                })
+                .collect();
+        }
+        score_board.update_impact();
+
+        renderer.render(&frame_scene(&effects, &score_board, &paddles, &ball));
+
+        for paddle in &mut paddles {
+            paddle.entity.set_display_scale(1.0);
+            paddle.entity.update_eye_timers();
+        }
+
+        effects.update(&ball, playing);
+
+        if playing {
+            max_ball_speed += 0.005;
+        }
+
+        // Wait for next frame
+        session.wait(delay_multiplier);
+        if delay_multiplier > 1 {
+            delay_multiplier = 1;
+
+            for paddle in &mut paddles {
+                paddle.entity.color = paddle_color(paddle.human, theme);
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+/// The window keys go to, the first human's paddle or the score board once they're all out
+pub fn input_window(seats: &[Seat; 4]) -> &'static str {
+    Edge::ALL
+        .into_iter()
+        .zip(*seats)
+        .find(|&(_, seat)| seat == Seat::Human)
+        .map_or("Score", |(edge, _)| edge.name())
+}
+
+/// A paddle at home on every seated edge
+fn seat_paddles(seats: &[Seat; 4], arena: Vector2f, theme: Theme) -> Vec<Paddle> {
+    let center = Vector2::new(arena.x / 2.0, arena.y / 2.0);
+    let inset = (PADDLE_WIDTH * 2) as f32;
+
+    Edge::ALL
+        .into_iter()
+        .zip(*seats)
+        .filter(|&(_, seat)| seat != Seat::Empty)
+        .map(|(edge, seat)| {
+            let home = match edge {
+                Edge::Left => Vector2::new(inset, center.y),
+                Edge::Right => Vector2::new(arena.x - inset, center.y),
+                Edge::Top => Vector2::new(center.x, TOP + inset),
+                Edge::Bottom => Vector2::new(center.x, arena.y - inset),
+            };
+            let (width, height) = if edge.upright() {
+                (PADDLE_WIDTH, PADDLE_LENGTH)
+            } else {
+                (PADDLE_LENGTH, PADDLE_WIDTH)
+            };
+            let human = seat == Seat::Human;
+            let mut entity = Entity::new(
+                home,
+                width,
+                height,
+                String::from(edge.name()),
+                paddle_color(human, theme),
+                0.75,
+                0.75,
+            );
+            entity.theme = theme;
+            Paddle {
+                edge,
+                human,
+                entity,
+                home,
+            }
+        })
+        .collect()
+}
+
+fn paddle_color(human: bool, theme: Theme) -> Color {
+    if human {
+        theme.player
+    } else {
+        theme.ai
+    }
+}
+
+/// Each seated edge's points or lives, two to a line
+fn score_text(paddles: &[Paddle], scores: &[u8; 4]) -> String {
+    let entries: Vec<String> = paddles
+        .iter()
+        .map(|paddle| format!("{} {}", paddle.edge.name(), scores[paddle.edge as usize]))
+        .collect();
+    entries
+        .chunks(2)
+        .map(|line| line.join("  "))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The score board at the bottom, then the paddles, then the ball on top
+fn frame_scene(
+    effects: &Effects,
+    score_board: &Entity,
+    paddles: &[Paddle],
+    ball: &Entity,
+) -> Scene {
+    let mut entities = vec![score_board];
+    entities.extend(paddles.iter().map(|paddle| &paddle.entity));
+    entities.push(ball);
+    scene(effects, &entities, ball)
+}
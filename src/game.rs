@@ -0,0 +1,1057 @@
+use std::str::FromStr;
+
+use sfml::{
+    graphics::Font,
+    system::{Vector2, Vector2f, Vector2i, Vector2u},
+    window::{Event, Key},
+};
+
+use crate::{
+    ai::{track_ball, AiShooter},
+    config::Config,
+    effects::Effects,
+    entity::Entity,
+    error::GameError,
+    input::{self, Control, MouseButton},
+    modes::{GameMode, MatchState, Wall},
+    motion::Mover,
+    records::RecordWatch,
+    render::{windows::measure_text, Renderer, Scene},
+    scoreboard::{ScoreBoardLayout, ScoreInfo},
+    serve::{Serve, Side},
+    session::Session,
+    shot::{smash_ball, Shooter},
+    stats::MatchStats,
+    theme::Theme,
+    utils::{normalize_vector, vector_length},
+};
+
+/// What closing one of the game's windows does
+#[derive(Clone, Copy, PartialEq)]
+pub enum ClosePolicy {
+    Quit,
+    // Pause the match, the window comes back straight away
+    Pause,
+    // Open the window again and keep playing
+    Respawn,
+}
+
+impl FromStr for ClosePolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "quit" => Ok(ClosePolicy::Quit),
+            "pause" => Ok(ClosePolicy::Pause),
+            "respawn" => Ok(ClosePolicy::Respawn),
+            _ => Err(format!(
+                "unknown close policy `{}` (expected quit, pause or respawn)",
+                value
+            )),
+        }
+    }
+}
+
+pub struct GameOptions {
+    // The play area, in desktop pixels
+    pub arena: Vector2u,
+    // Let the AI move and serve for the player
+    pub autopilot: bool,
+    // Wait between frames, off to play as fast as possible
+    pub realtime: bool,
+    // Stop after this many matches, otherwise play until quitting
+    pub matches: Option<u32>,
+    // Print hits and points as they happen
+    pub log_events: bool,
+}
+
+pub struct MatchResult {
+    pub winner: Side,
+    pub score: (u8, u8),
+}
+
+/// Plays by the rules of `mode` until the windows are closed or enough matches are over,
+/// returning how each one went
+pub fn run(
+    config: &Config,
+    renderer: &mut dyn Renderer,
+    font: Option<&Font>,
+    match_stats: &mut MatchStats,
+    record_watch: &mut RecordWatch,
+    mode: &mut dyn GameMode,
+    options: &GameOptions,
+) -> Result<Vec<MatchResult>, GameError> {
+    let mut playing: bool = false;
+
+    const MAX_PLAYER_SPEED: f32 = 15.0;
+    const INIT_BALL_SPEED: f32 = 20.0;
+
+    let mut up_pressed: bool = false;
+    let mut down_pressed: bool = false;
+    let mut left_pressed: bool = false;
+    let mut right_pressed: bool = false;
+
+    const PLAYER_WINDOW_WIDTH: u32 = 75;
+    const PLAYER_WINDOW_HEIGHT: u32 = 300;
+
+    const SCORE_BOARD_WIDTH: u32 = 250;
+    const SCORE_BOARD_HEIGHT: u32 = 100;
+
+    let ball_center_pos: Vector2<f32> =
+        Vector2::new((options.arena.x / 2) as f32, (options.arena.y / 2) as f32);
+
+    let mut input: i8;
+
+    let mut player_shot = Shooter::new(config.smash_mode, config.smash_charge_frames);
+    let mut ai_shot = Shooter::new(config.smash_mode, config.smash_charge_frames);
+    let mut ai_shooter = AiShooter::new(config.ai_difficulty, config.smash_charge_frames);
+    let mut player_shooter = options
+        .autopilot
+        .then(|| AiShooter::new(config.ai_difficulty, config.smash_charge_frames));
+    let mut late_smash: Option<f32>;
+
+    let paddle_sizing = &config.paddle_sizing;
+
+    let mut effects = Effects::new(&config.effects, config.theme.flash, options.arena);
+
+    let mut state = MatchState {
+        score: (0, 0),
+        ball_speed: 15.0,
+        frames: 0,
+        balls: 1,
+        new_balls: Vec::new(),
+        arena: options.arena,
+    };
+    let opponent = mode.has_opponent();
+
+    let mut serve = Serve::new(config.serve_rule, config.serve_ai_delay);
+
+    const IMPACT_SCALE: f32 = 1.1;
+    const SCORE_IMPACT_FORCE: f32 = 30.0;
+
+    let theme = config.theme;
+
+    // Ball setup
+
+    // The first ball is the one served, any more are put into play by the mode
+    let mut balls: Vec<Entity> = vec![new_ball(ball_center_pos, String::from("Ball"), theme)];
+    // Balls put into play so far, to give each its own window
+    let mut balls_added: u32 = 1;
+
+    // Score window setup
+
+    let mut score_board = Entity::new(
+        Vector2::new((options.arena.x / 2) as f32, 150.0),
+        SCORE_BOARD_WIDTH,
+        SCORE_BOARD_HEIGHT,
+        String::from("Score"),
+        theme.score_board,
+        0.5,
+        0.85,
+    );
+    score_board.has_eye = false;
+
+    let score_layout = &config.score_board_layout;
+    score_board.text_size = score_layout.text_size;
+    // Modes with their own text have a few lines of it to fit
+    if mode.score_text(&state).is_some() {
+        score_board.text_size /= 2;
+    }
+    score_board.text_align = score_layout.align;
+    score_board.text_padding = score_layout.padding as f32;
+
+    let mut score_board_mover = Mover::new(
+        config.score_board_motion,
+        config.score_board_speed,
+        config.score_board_range,
+    );
+
+    // Player setup
+
+    let mut player: Entity = Entity::new(
+        Vector2::new(
+            (PLAYER_WINDOW_WIDTH + PLAYER_WINDOW_WIDTH) as f32,
+            (options.arena.y / 2) as f32,
+        ),
+        PLAYER_WINDOW_WIDTH,
+        PLAYER_WINDOW_HEIGHT,
+        String::from("Player"),
+        theme.player,
+        0.75,
+        0.75,
+    );
+
+    // AI setup
+
+    let mut ai: Entity = Entity::new(
+        Vector2::new(
+            (options.arena.x - PLAYER_WINDOW_WIDTH - PLAYER_WINDOW_WIDTH) as f32,
+            (options.arena.y / 2) as f32,
+        ),
+        PLAYER_WINDOW_WIDTH,
+        PLAYER_WINDOW_HEIGHT,
+        String::from("AI"),
+        theme.ai,
+        0.75,
+        0.75,
+    );
+
+    for entity in [&mut score_board, &mut player, &mut ai] {
+        entity.theme = theme;
+    }
+    player.steady = config.control == Control::Drag;
+
+    // Where the paddles start and go back to after each point, and where they can wander
+    let player_home_x = player.position.x;
+    let ai_home_x = ai.position.x;
+    let zone_width = options.arena.x as f32 * config.movement_zone;
+    let player_zone = (0.0, zone_width);
+    let ai_zone = (options.arena.x as f32 - zone_width, options.arena.x as f32);
+
+    renderer.open(&Scene {
+        windows: with_windows(&score_board, [&player, &ai], &balls, opponent)
+            .into_iter()
+            .map(|entity| entity.scene_window())
+            .collect(),
+    })?;
+
+    // Game loop
+
+    let mut session = Session::new(config, options.realtime);
+    let mut delay_multiplier: u32 = 1;
+
+    let mut results = Vec::new();
+    let mut mouse_button = MouseButton::default();
+    // Where the player's window was last drawn, to tell how far it's been dragged
+    let mut shown_player: Option<Vector2i> = None;
+
+    while session.running {
+        late_smash = None;
+
+        // Event handling
+        // Shoot presses (true) and releases (false), from space or the mouse
+        let mut shots: Vec<bool> = Vec::new();
+        for evt in session.poll_events(renderer) {
+            match evt {
+                Event::KeyPressed { code, .. } => match code {
+                    Key::Up => {
+                        up_pressed = true;
+                    }
+                    Key::Down => {
+                        down_pressed = true;
+                    }
+                    Key::Left => {
+                        left_pressed = true;
+                    }
+                    Key::Right => {
+                        right_pressed = true;
+                    }
+                    Key::Space => shots.push(true),
+                    _ => {}
+                },
+                Event::KeyReleased { code, .. } => match code {
+                    Key::Up => {
+                        up_pressed = false;
+                    }
+                    Key::Down => {
+                        down_pressed = false;
+                    }
+                    Key::Left => {
+                        left_pressed = false;
+                    }
+                    Key::Right => {
+                        right_pressed = false;
+                    }
+                    Key::Space => shots.push(false),
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+
+        // Paused matches only keep their windows drawn
+        if session.is_paused() {
+            let score_text = match mode.score_text(&state) {
+                Some(text) => format!("{}\nPaused", text),
+                None => score_layout.text(&ScoreInfo {
+                    score: shown_score(state.score, config.lives),
+                    server: (!playing).then_some(serve.server),
+                    elapsed: session.elapsed(),
+                    clock: mode.clock(&state),
+                    notice: Some(String::from("Paused")),
+                }),
+            };
+            set_score_text(
+                &mut score_board,
+                score_layout,
+                font,
+                score_text,
+                Vector2::new(SCORE_BOARD_WIDTH, SCORE_BOARD_HEIGHT),
+            );
+            renderer.render(&scene(
+                &effects,
+                &with_windows(&score_board, [&player, &ai], &balls, opponent),
+                &balls[0],
+            ));
+
+            // Drags while paused don't count, the paddle goes back to where it was
+            shown_player = None;
+
+            session.wait(1);
+            continue;
+        }
+
+        if config.control != Control::Keyboard {
+            if let Some(pressed) = mouse_button.update() {
+                shots.push(pressed);
+            }
+        }
+
+        // Some modes serve the ball themselves, instead of whoever's turn it is
+        let mode_serve = if playing {
+            None
+        } else {
+            mode.on_serve(&mut state, &mut balls[0])
+        };
+        if mode_serve == Some(true) {
+            if options.log_events {
+                println!("Launching!");
+            }
+            playing = true;
+        }
+
+        // Shooting, or serving while the ball isn't in play
+        for pressed in shots {
+            match (pressed, playing) {
+                (true, false) if serve.server == Side::Player && mode_serve.is_none() => {
+                    serve.aiming = true
+                }
+                (true, true) => {
+                    late_smash = player_shot.press();
+                    mode.on_shot();
+                }
+                (false, false) if serve.aiming => {
+                    if options.log_events {
+                        println!("Starting the game!");
+                    }
+                    serve.launch(&mut balls[0], INIT_BALL_SPEED);
+                    playing = true;
+                }
+                (false, true) => late_smash = player_shot.release(),
+                _ => {}
+            }
+        }
+
+        // Input handling
+
+        input = 0;
+
+        if up_pressed {
+            input -= 1;
+        }
+        if down_pressed {
+            input += 1;
+        }
+
+        // While aiming a serve, up and down turn the aim instead of moving the paddle
+        if config.control == Control::Mouse {
+            if let Some(cursor) = renderer.mouse_position() {
+                input = input::follow(player.position.y, cursor.y);
+            }
+        }
+
+        if serve.aiming {
+            serve.aim(input);
+            input = 0;
+        }
+
+        // Player Logic
+
+        // However far the window was dragged this frame is how fast the paddle is going
+        let drag = (config.control == Control::Drag).then(|| {
+            match (renderer.dragged_position("Player"), shown_player) {
+                (Some(dragged), Some(shown)) => dragged - shown,
+                _ => Vector2::new(0, 0),
+            }
+        });
+
+        if options.autopilot {
+            if let Some(ball) = incoming(&balls, Side::Player) {
+                track_ball(&mut player, ball, MAX_PLAYER_SPEED);
+            }
+        } else if let (Control::MouseDirect, Some(cursor)) =
+            (config.control, renderer.mouse_position())
+        {
+            jump(
+                &mut player.position.y,
+                &mut player.velocity.y,
+                cursor.y,
+                MAX_PLAYER_SPEED,
+            );
+        } else if let Some(drag) = drag {
+            let target = player.position.y + drag.y as f32;
+            jump(
+                &mut player.position.y,
+                &mut player.velocity.y,
+                target,
+                MAX_PLAYER_SPEED,
+            );
+        } else if input == 0 {
+            // Decelerating
+            let velocity_sign = player.velocity.y.signum();
+            player.acceleration.y = -velocity_sign;
+            player.velocity.y += player.acceleration.y;
+            player.velocity.y =
+                player.velocity.y.abs().clamp(0.0, MAX_PLAYER_SPEED) * velocity_sign;
+            if player.velocity.y.abs() < 2.5 {
+                player.velocity.y = 0.0;
+            }
+        } else {
+            // Accelerating
+            player.acceleration.y = input as f32 * 1.0;
+            player.velocity.y += player.acceleration.y;
+        }
+
+        // Sideways, inside the player's zone
+        if config.free_movement && !options.autopilot {
+            let input_x = match (config.control, renderer.mouse_position(), drag) {
+                (Control::MouseDirect, Some(cursor), _) => {
+                    jump(
+                        &mut player.position.x,
+                        &mut player.velocity.x,
+                        cursor.x,
+                        MAX_PLAYER_SPEED,
+                    );
+                    None
+                }
+                (_, _, Some(drag)) => {
+                    let target = player.position.x + drag.x as f32;
+                    jump(
+                        &mut player.position.x,
+                        &mut player.velocity.x,
+                        target,
+                        MAX_PLAYER_SPEED,
+                    );
+                    None
+                }
+                (Control::Mouse, Some(cursor), _) => {
+                    Some(input::follow(player.position.x, cursor.x))
+                }
+                _ => Some(right_pressed as i8 - left_pressed as i8),
+            };
+            if let Some(input_x) = input_x {
+                accelerate(
+                    &mut player.velocity.x,
+                    &mut player.acceleration.x,
+                    input_x,
+                    MAX_PLAYER_SPEED,
+                );
+            }
+        }
+
+        player.r#move();
+
+        if player.position.y < 48.0 + player.half_size.y as f32
+            || player.position.y > options.arena.y as f32 - player.half_size.y as f32
+        {
+            player.velocity.y = 0.0;
+
+            let clamped_player_pos_y: f32 = player.position.y.clamp(
+                (48 + player.half_size.y) as f32,
+                (options.arena.y - player.half_size.y) as f32,
+            );
+
+            player.set_position(None, Some(clamped_player_pos_y));
+        }
+        if config.free_movement {
+            keep_in_zone(&mut player, player_zone);
+        }
+
+        // AI Logic
+
+        let ai_ball = incoming(&balls, Side::Ai);
+        if let Some(ball) = ai_ball {
+            track_ball(&mut ai, ball, MAX_PLAYER_SPEED);
+        }
+
+        // Step up to meet the ball once it's in the AI's zone, then drop back
+        if config.free_movement {
+            let target_x = match ai_ball {
+                Some(ball) if ball.position.x > ai_zone.0 => {
+                    ball.position.x + (ball.half_size.x + ai.half_size.x) as f32
+                }
+                _ => ai_home_x,
+            };
+            accelerate(
+                &mut ai.velocity.x,
+                &mut ai.acceleration.x,
+                input::follow(ai.position.x, target_x),
+                MAX_PLAYER_SPEED,
+            );
+        }
+
+        if ai.position.y < 48.0 + ai.half_size.y as f32
+            || ai.position.y > options.arena.y as f32 - ai.half_size.y as f32
+        {
+            ai.velocity.y = 0.0;
+
+            let clamped_ai_pos_y: f32 = ai.position.y.clamp(
+                (48 + ai.half_size.y) as f32,
+                (options.arena.y - ai.half_size.y) as f32,
+            );
+
+            ai.set_position(None, Some(clamped_ai_pos_y));
+        }
+
+        ai.r#move();
+        if config.free_movement {
+            keep_in_zone(&mut ai, ai_zone);
+        }
+
+        let ai_late_smash = if playing && opponent {
+            let ball = incoming(&balls, Side::Ai).unwrap_or(&balls[0]);
+            ai_shooter.update(&ai, ball, &mut ai_shot)
+        } else {
+            None
+        };
+        if let (true, Some(shooter)) = (playing, player_shooter.as_mut()) {
+            let ball = incoming(&balls, Side::Player).unwrap_or(&balls[0]);
+            late_smash = shooter.update(&player, ball, &mut player_shot);
+        }
+
+        // Shooting just after the ball left the paddle still smashes it
+        for (smash, side, paddle) in [
+            (late_smash, Side::Player, &mut player),
+            (ai_late_smash, Side::Ai, &mut ai),
+        ] {
+            if let Some(power) = smash {
+                mode.on_late_smash(side);
+                let index = nearest(&balls, paddle);
+                let ball = &mut balls[index];
+                smash_ball(ball, state.ball_speed * power);
+                match_stats.on_smash(side, vector_length(ball.velocity));
+                record_watch.on_late_smash(side);
+                delay_multiplier = 5;
+                effects.on_hit(paddle, ball, true);
+                paddle.color = theme.smash;
+                paddle.set_display_scale(IMPACT_SCALE)
+            }
+        }
+
+        // Score board logic
+
+        score_board_mover.update(&mut score_board, state.score);
+
+        // Serve logic
+
+        if !playing && mode_serve.is_none() {
+            let ball = &mut balls[0];
+            // The AI aims before the ball is placed, so it's held where it'll be served
+            let (server, ready) = match serve.server {
+                Side::Player => (&player, options.autopilot && serve.ai_ready()),
+                Side::Ai => (&ai, serve.ai_ready()),
+            };
+            serve.place_ball(ball, server);
+            if ready {
+                if options.log_events {
+                    println!("Starting the game!");
+                }
+                serve.launch(ball, INIT_BALL_SPEED);
+                playing = true;
+            }
+        }
+
+        // Ball Logic
+
+        if playing {
+            state.balls = balls.len();
+
+            // Every ball in play moves and bounces the same way
+            let mut index = 0;
+            while index < balls.len() {
+                let ball = &mut balls[index];
+                ball.r#move();
+
+                let other_entities = [&mut player, &mut ai, &mut score_board];
+
+                // Ball collision
+
+                for entity in other_entities {
+                    if !opponent && entity.name == "AI" {
+                        continue;
+                    }
+                    let ball_player_overlap: Vector2<i32> = Entity::get_overlap(ball, entity);
+                    if ball_player_overlap.x > 0 && ball_player_overlap.y > 0 {
+                        if options.log_events {
+                            println!("{} collided with {}!", ball.name, entity.name);
+                        }
+
+                        bounce_off(ball, entity, ball_player_overlap, state.ball_speed);
+
+                        let mut impact_force = Vector2::new(ball.velocity.x, ball.velocity.y);
+
+                        let side = match entity.name.as_str() {
+                            "Player" => Some(Side::Player),
+                            "AI" => Some(Side::Ai),
+                            _ => None,
+                        };
+                        let shooter = match side {
+                            Some(Side::Player) => Some(&mut player_shot),
+                            Some(Side::Ai) => Some(&mut ai_shot),
+                            None => None,
+                        };
+
+                        let smash = shooter.and_then(|shooter| shooter.on_return());
+                        if let Some(power) = smash {
+                            smash_ball(ball, state.ball_speed * power);
+                            impact_force *= 2.0 * power;
+                            delay_multiplier = 5;
+                        } else {
+                            ball.color = theme.ball;
+                            delay_multiplier = 3;
+                        }
+                        if let Some(side) = side {
+                            effects.on_hit(entity, ball, smash.is_some());
+
+                            let ball_speed = vector_length(ball.velocity);
+                            match_stats.on_return(side, ball_speed);
+                            if smash.is_some() {
+                                match_stats.on_smash(side, ball_speed);
+                            }
+                            record_watch.on_return(side, smash.is_some());
+                            mode.on_hit(&mut state, side, entity, ball, smash.is_some());
+                        }
+                        entity.color = ball.color;
+
+                        entity.impact(&impact_force);
+                    }
+                }
+
+                if (ball.position.y - ball.half_size.y as f32) < 48.0
+                    || ball.position.y + ball.half_size.y as f32 > options.arena.y as f32
+                {
+                    if options.log_events {
+                        println!("{} collided with the edge of the screen!", ball.name);
+                    }
+                    ball.velocity.y *= -1.0;
+                    let clamped_ball_pos_y: f32 = ball.position.y.clamp(
+                        (48 + ball.half_size.y) as f32,
+                        (options.arena.y - ball.half_size.y) as f32,
+                    );
+                    ball.set_position(None, Some(clamped_ball_pos_y));
+                }
+
+                // Past the player's paddle on the left, or the AI's on the right
+                let side = if (ball.position.x - ball.half_size.x as f32) < 0.0 {
+                    Side::Player
+                } else if ball.position.x + ball.half_size.x as f32 > options.arena.x as f32 {
+                    Side::Ai
+                } else {
+                    index += 1;
+                    continue;
+                };
+
+                let wall = mode.on_wall(&mut state, side);
+                if wall == Wall::Bounce {
+                    if options.log_events {
+                        println!("{} collided with the wall!", ball.name);
+                    }
+                    let (x, away) = match side {
+                        Side::Player => (ball.half_size.x as f32, 1.0),
+                        Side::Ai => ((options.arena.x - ball.half_size.x) as f32, -1.0),
+                    };
+                    ball.velocity.x = away * ball.velocity.x.abs();
+                    ball.set_position(Some(x), None);
+                    index += 1;
+                    continue;
+                }
+
+                if wall == Wall::Point {
+                    if options.log_events {
+                        println!("Point!");
+                    }
+
+                    match side {
+                        Side::Player => state.score.1 += 1,
+                        Side::Ai => state.score.0 += 1,
+                    }
+                    serve.next_server(side);
+                    effects.on_point(side);
+                    record_watch.on_point(match_stats.rally(), state.ball_speed);
+                    match_stats.on_point();
+                    mode.on_point(&mut state, side, [&mut player, &mut ai]);
+                } else if options.log_events {
+                    println!("{} is out!", balls[index].name);
+                }
+
+                // Play goes on while there are other balls, unless that was the match
+                if balls.len() > 1 && mode.is_match_over(&state).is_none() {
+                    balls.remove(index);
+                    continue;
+                }
+
+                let out_at = balls[index].position;
+                balls.truncate(1);
+                let ball = &mut balls[0];
+                playing = false;
+                ball.velocity = Vector2::new(0.0, 0.0);
+                ball.color = theme.ball;
+                state.ball_speed = INIT_BALL_SPEED;
+
+                if wall == Wall::Out {
+                    if side == Side::Player {
+                        // Nobody scores a miss in practice, but the paddle still starts over
+                        mode.on_point(&mut state, side, [&mut player, &mut ai]);
+                        player_shot.reset();
+                        if config.control != Control::Drag {
+                            player.velocity.x = 0.0;
+                            player.set_position(
+                                Some(player_home_x),
+                                Some((options.arena.y / 2) as f32 - 24.0),
+                            );
+                        }
+                        delay_multiplier = 30;
+                    }
+                    break;
+                }
+
+                player_shot.reset();
+                ai_shot.reset();
+                ai_shooter.reset();
+                // A dragged paddle stays in the player's hand
+                if config.control != Control::Drag {
+                    player.velocity.x = 0.0;
+                    player.set_position(
+                        Some(player_home_x),
+                        Some((options.arena.y / 2) as f32 - 24.0),
+                    );
+                }
+
+                let ball_player_delta = out_at - player.position;
+                player.impact(
+                    &((Vector2::new(1.0, 1.0) - (ball_player_delta / options.arena.x as f32))
+                        * SCORE_IMPACT_FORCE),
+                );
+
+                ai.velocity = Vector2::new(0.0, 0.0);
+                ai.set_position(Some(ai_home_x), Some((options.arena.y / 2) as f32 - 24.0));
+                let ball_ai_delta = out_at - ai.position;
+                ai.impact(
+                    &((Vector2::new(1.0, 1.0) - (ball_ai_delta / options.arena.x as f32))
+                        * SCORE_IMPACT_FORCE),
+                );
+
+                delay_multiplier = 30;
+                break;
+            }
+        }
+
+        // Balls the rules put into play, once the rally's still going
+        for (position, velocity) in state.new_balls.drain(..) {
+            if playing {
+                balls_added += 1;
+                let mut ball = new_ball(position, format!("Ball {}", balls_added), theme);
+                ball.velocity = velocity;
+                balls.push(ball);
+            }
+        }
+
+        // Match logic, after a point or once time's up between points
+        if let (false, Some(winner)) = (playing, mode.is_match_over(&state)) {
+            let score = state.score;
+            let winner_name = match winner {
+                Side::Player => &score_layout.player_name,
+                Side::Ai => &score_layout.ai_name,
+            };
+            if options.log_events {
+                println!("{} wins {} - {}!", winner_name, score.0, score.1);
+            }
+
+            match_stats.on_match_end(winner);
+            if winner == Side::Player {
+                record_watch.on_win(session.elapsed().as_secs_f32());
+            }
+            results.push(MatchResult { winner, score });
+            if options
+                .matches
+                .is_some_and(|matches| results.len() as u32 >= matches)
+            {
+                session.running = false;
+            }
+            state.score = (0, 0);
+            state.frames = 0;
+            session.restart_clock();
+        }
+
+        // Player display
+        player.charge = player_shot.charge();
+        player.size_meter = paddle_sizing.meter(&player);
+        player.update_impact();
+        player.update_eye(
+            &score_board,
+            incoming(&balls, Side::Player).unwrap_or(&balls[0]),
+        );
+
+        // AI display
+        ai.charge = ai_shot.charge();
+        ai.size_meter = paddle_sizing.meter(&ai);
+        ai.update_impact();
+        ai.update_eye(
+            &score_board,
+            incoming(&balls, Side::Ai).unwrap_or(&balls[0]),
+        );
+
+        // Score window logic
+
+        let score_text = mode.score_text(&state).unwrap_or_else(|| {
+            score_layout.text(&ScoreInfo {
+                score: shown_score(state.score, config.lives),
+                server: (!playing).then_some(serve.server),
+                elapsed: session.elapsed(),
+                clock: mode.clock(&state),
+                notice: record_watch.notice().map(|(notice, _)| notice),
+            })
+        });
+        set_score_text(
+            &mut score_board,
+            score_layout,
+            font,
+            score_text,
+            Vector2::new(SCORE_BOARD_WIDTH, SCORE_BOARD_HEIGHT),
+        );
+
+        if config.lives > 0 {
+            let lives = shown_score(state.score, config.lives);
+            score_board.lives = vec![(lives.0, theme.player), (lives.1, theme.ai)];
+        }
+
+        // Blink while a new record is announced
+        if let Some((_, frames)) = record_watch.notice() {
+            score_board.color = if frames > 1 && frames / 15 % 2 == 0 {
+                theme.smash
+            } else {
+                theme.score_board
+            };
+        }
+
+        score_board.update_impact();
+
+        // Display
+
+        let frame = scene(
+            &effects,
+            &with_windows(&score_board, [&player, &ai], &balls, opponent),
+            &balls[0],
+        );
+        renderer.render(&frame);
+        shown_player = frame
+            .windows
+            .iter()
+            .find(|window| window.name == "Player")
+            .map(|window| window.position);
+
+        player.set_display_scale(1.0);
+        ai.set_display_scale(1.0);
+        score_board.set_display_scale(1.0);
+
+        // Timers
+        player_shot.update_timers();
+        ai_shot.update_timers();
+
+        player.update_eye_timers();
+        ai.update_eye_timers();
+
+        effects.update(&balls[0], playing);
+        record_watch.update();
+
+        // The match clock starts with the first serve
+        if playing || state.frames > 0 {
+            state.frames = state.frames.saturating_add(delay_multiplier);
+        }
+        mode.on_tick(
+            &mut state,
+            playing,
+            delay_multiplier,
+            [&mut player, &mut ai],
+        );
+
+        // Wait for next frame
+        session.wait(delay_multiplier);
+        if delay_multiplier > 1 {
+            delay_multiplier = 1;
+
+            player.color = theme.player;
+            ai.color = theme.ai;
+            score_board.color = theme.score_board;
+        }
+    }
+
+    Ok(results)
+}
+
+/// Sends the ball off whatever it overlaps by `overlap`, picking up that thing's speed
+pub fn bounce_off(ball: &mut Entity, entity: &Entity, overlap: Vector2<i32>, speed: f32) {
+    let prev_overlap: Vector2<i32> = Entity::get_prev_overlap(ball, entity);
+    let adjustment_sign: Vector2<f32> = Vector2::new(
+        if entity.position.x > ball.position.x {
+            1.0
+        } else {
+            -1.0
+        },
+        if entity.position.y < ball.position.y {
+            1.0
+        } else {
+            -1.0
+        },
+    );
+    // Sent away from whatever it hit, in case that was moving faster than the ball
+    let away_x = ball.velocity.x.abs() * -adjustment_sign.x;
+    let mut side_hit = false;
+    if prev_overlap.y > 0 {
+        ball.velocity.x = away_x;
+        side_hit = true;
+        let adjustment: f32 = ball.position.x - (overlap.x as f32 * adjustment_sign.x);
+        ball.set_position(Some(adjustment), None);
+    } else if prev_overlap.x > 0 {
+        ball.velocity.y *= -1.0;
+        let adjustment: f32 = ball.position.y + (overlap.y as f32 * adjustment_sign.y);
+        ball.set_position(None, Some(adjustment));
+    } else {
+        if overlap.y >= overlap.x {
+            ball.velocity.x = away_x;
+            side_hit = true;
+            let adjustment = ball.position.x - (overlap.x as f32 * adjustment_sign.x);
+            ball.set_position(Some(adjustment), None);
+        } else {
+            ball.velocity.y *= -1.0;
+            let adjustment = ball.position.y - (overlap.y as f32 * adjustment_sign.y);
+            ball.set_position(None, Some(adjustment));
+        }
+    }
+
+    ball.velocity += entity.velocity;
+    // A paddle backing off sideways can't pull the ball through itself
+    if side_hit && ball.velocity.x.signum() != away_x.signum() {
+        ball.velocity.x = -ball.velocity.x;
+    }
+    ball.velocity = normalize_vector(ball.velocity) * speed;
+}
+
+/// The score as the score board shows it, counting down each side's lives when playing with them
+fn shown_score(score: (u8, u8), lives: u8) -> (u8, u8) {
+    if lives == 0 {
+        return score;
+    }
+    (lives.saturating_sub(score.1), lives.saturating_sub(score.0))
+}
+
+/// Speeds a paddle up along one axis while `input` is held, and slows it to a stop after
+pub fn accelerate(velocity: &mut f32, acceleration: &mut f32, input: i8, max_speed: f32) {
+    if input == 0 {
+        let velocity_sign = velocity.signum();
+        *acceleration = -velocity_sign;
+        *velocity += *acceleration;
+        *velocity = velocity.abs().clamp(0.0, max_speed) * velocity_sign;
+        if velocity.abs() < 2.5 {
+            *velocity = 0.0;
+        }
+    } else {
+        *acceleration = input as f32;
+        *velocity = (*velocity + *acceleration).clamp(-max_speed, max_speed);
+    }
+}
+
+/// Sets a paddle up to reach `target` along one axis with its next move. Only up to
+/// `max_speed` of the way is velocity, which is what hits feel, the rest is jumped straight away
+fn jump(position: &mut f32, velocity: &mut f32, target: f32, max_speed: f32) {
+    *velocity = (target - *position).clamp(-max_speed, max_speed);
+    *position = target - *velocity;
+}
+
+/// Stops a paddle at the edges of the strip between `zone`'s left and right edges
+fn keep_in_zone(paddle: &mut Entity, zone: (f32, f32)) {
+    let min = zone.0 + paddle.half_size.x as f32;
+    let max = (zone.1 - paddle.half_size.x as f32).max(min);
+    if paddle.position.x < min || paddle.position.x > max {
+        paddle.velocity.x = 0.0;
+        paddle.set_position(Some(paddle.position.x.clamp(min, max)), None);
+    }
+}
+
+/// Changes the score board's text, fitting the window around it if it's meant to
+pub fn set_score_text(
+    score_board: &mut Entity,
+    layout: &ScoreBoardLayout,
+    font: Option<&Font>,
+    text: String,
+    min_size: Vector2u,
+) {
+    if score_board.text.as_ref() == Some(&text) {
+        return;
+    }
+
+    // Nothing to measure with when nothing is drawn
+    if let (true, Some(font)) = (layout.auto_resize, font) {
+        let size = layout.fit(measure_text(font, &text, layout.text_size), min_size);
+        score_board.set_size(size.x, size.y);
+    }
+    score_board.text = Some(text);
+}
+
+/// The entities that get a window, which leaves out the AI when there's nobody to play against
+fn with_windows<'a>(
+    score_board: &'a Entity,
+    paddles: [&'a Entity; 2],
+    balls: &'a [Entity],
+    opponent: bool,
+) -> Vec<&'a Entity> {
+    let [player, ai] = paddles;
+    let mut entities = vec![score_board, player];
+    if opponent {
+        entities.push(ai);
+    }
+    entities.extend(balls);
+    entities
+}
+
+fn new_ball(position: Vector2f, name: String, theme: Theme) -> Entity {
+    const BALL_SIDE: u32 = 100;
+
+    let mut ball = Entity::new(position, BALL_SIDE, BALL_SIDE, name, theme.ball, 1.0, 1.0);
+    ball.has_eye = false;
+    ball.theme = theme;
+    ball
+}
+
+/// Of the balls heading for `side`'s edge, the one that'll get there first
+fn incoming(balls: &[Entity], side: Side) -> Option<&Entity> {
+    let toward = match side {
+        Side::Player => -1.0,
+        Side::Ai => 1.0,
+    };
+    balls
+        .iter()
+        .filter(|ball| ball.velocity.x * toward > 0.0)
+        .max_by(|a, b| (a.position.x * toward).total_cmp(&(b.position.x * toward)))
+}
+
+/// The ball closest to a paddle, the one it last hit when shooting just after a return
+fn nearest(balls: &[Entity], paddle: &Entity) -> usize {
+    let distance = |ball: &Entity| (ball.position.x - paddle.position.x).abs();
+    balls
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| distance(a).total_cmp(&distance(b)))
+        .map_or(0, |(index, _)| index)
+}
+
+/// Every window on screen this frame, decorated with the effects
+pub fn scene(effects: &Effects, entities: &[&Entity], ball: &Entity) -> Scene {
+    let mut windows = effects.windows(ball);
+    for entity in entities {
+        let mut window = entity.scene_window();
+        effects.decorate(&mut window, ball);
+        windows.push(window);
+    }
+    Scene { windows }
+}
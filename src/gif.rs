@@ -0,0 +1,154 @@
+//! Minimal animated GIF writer for captured frames
+
+use std::io::{self, Write};
+
+use sfml::system::Vector2u;
+
+// Channel levels of the 6x6x6 color cube every frame is snapped to
+const LEVELS: u32 = 6;
+const MAX_CODE_SIZE: u32 = 12;
+
+pub struct GifWriter<W: Write> {
+    output: W,
+    size: Vector2u,
+}
+
+impl<W: Write> GifWriter<W> {
+    /// Writes the header and a global palette, looping the animation forever
+    pub fn new(mut output: W, size: Vector2u) -> io::Result<GifWriter<W>> {
+        output.write_all(b"GIF89a")?;
+        output.write_all(&(size.x as u16).to_le_bytes())?;
+        output.write_all(&(size.y as u16).to_le_bytes())?;
+        // Global color table of 256 entries, 8 bits per channel
+        output.write_all(&[0xF7, 0, 0])?;
+
+        for index in 0..256u32 {
+            let color = if index < LEVELS * LEVELS * LEVELS {
+                let level = |value: u32| (value * 255 / (LEVELS - 1)) as u8;
+                [
+                    level(index / (LEVELS * LEVELS)),
+                    level(index / LEVELS % LEVELS),
+                    level(index % LEVELS),
+                ]
+            } else {
+                [0, 0, 0]
+            };
+            output.write_all(&color)?;
+        }
+
+        // Netscape extension to loop forever
+        output.write_all(&[0x21, 0xFF, 0x0B])?;
+        output.write_all(b"NETSCAPE2.0")?;
+        output.write_all(&[0x03, 0x01, 0x00, 0x00, 0x00])?;
+
+        Ok(GifWriter { output, size })
+    }
+
+    /// Adds a frame of RGBA pixels, shown for `delay` hundredths of a second
+    pub fn write_frame(&mut self, pixels: &[u8], delay: u16) -> io::Result<()> {
+        // Graphic control extension for the frame delay
+        self.output.write_all(&[0x21, 0xF9, 0x04, 0x00])?;
+        self.output.write_all(&delay.to_le_bytes())?;
+        self.output.write_all(&[0x00, 0x00])?;
+
+        // Image descriptor covering the whole canvas
+        self.output.write_all(&[0x2C, 0, 0, 0, 0])?;
+        self.output.write_all(&(self.size.x as u16).to_le_bytes())?;
+        self.output.write_all(&(self.size.y as u16).to_le_bytes())?;
+        self.output.write_all(&[0x00])?;
+
+        let indices: Vec<u8> = pixels
+            .chunks_exact(4)
+            .map(|pixel| {
+                let level = |value: u8| (value as u32 * (LEVELS - 1) + 127) / 255;
+                (level(pixel[0]) * LEVELS * LEVELS + level(pixel[1]) * LEVELS + level(pixel[2]))
+                    as u8
+            })
+            .collect();
+
+        self.output.write_all(&[8])?;
+        for block in lzw_compress(&indices).chunks(255) {
+            self.output.write_all(&[block.len() as u8])?;
+            self.output.write_all(block)?;
+        }
+        self.output.write_all(&[0x00])
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        self.output.write_all(&[0x3B])?;
+        self.output.flush()
+    }
+}
+
+/// GIF flavoured LZW, starting from 8-bit codes
+fn lzw_compress(indices: &[u8]) -> Vec<u8> {
+    const CLEAR: u32 = 256;
+    const END: u32 = 257;
+
+    let mut bytes = Vec::new();
+    let mut bit_buffer = 0u32;
+    let mut bit_count = 0u32;
+    let mut write_code = |code: u32, size: u32, bytes: &mut Vec<u8>| {
+        bit_buffer |= code << bit_count;
+        bit_count += size;
+        while bit_count >= 8 {
+            bytes.push(bit_buffer as u8);
+            bit_buffer >>= 8;
+            bit_count -= 8;
+        }
+    };
+
+    // Codes for each (prefix code, next index) pair seen so far
+    let mut table = vec![u16::MAX; 4096 * 256];
+    let mut next_code = END + 1;
+    let mut code_size = 9;
+
+    write_code(CLEAR, code_size, &mut bytes);
+
+    let Some((first, rest)) = indices.split_first() else {
+        write_code(END, code_size, &mut bytes);
+        if bit_count > 0 {
+            bytes.push(bit_buffer as u8);
+        }
+        return bytes;
+    };
+
+    let mut prefix = *first as u32;
+    for index in rest {
+        let entry = (prefix as usize) * 256 + *index as usize;
+        if table[entry] != u16::MAX {
+            prefix = table[entry] as u32;
+            continue;
+        }
+
+        write_code(prefix, code_size, &mut bytes);
+
+        // Clear one code early, like giflib, so every decoder agrees on when the table is full
+        if next_code < (1 << MAX_CODE_SIZE) - 1 {
+            table[entry] = next_code as u16;
+            // Widen once the new code no longer fits
+            if next_code == 1 << code_size && code_size < MAX_CODE_SIZE {
+                code_size += 1;
+            }
+            next_code += 1;
+        } else {
+            write_code(CLEAR, code_size, &mut bytes);
+            table.fill(u16::MAX);
+            next_code = END + 1;
+            code_size = 9;
+        }
+
+        prefix = *index as u32;
+    }
+
+    write_code(prefix, code_size, &mut bytes);
+    // The decoder adds an entry for the last code too, which can widen the end code
+    if next_code == 1 << code_size && code_size < MAX_CODE_SIZE {
+        code_size += 1;
+    }
+    write_code(END, code_size, &mut bytes);
+    if bit_count > 0 {
+        bytes.push(bit_buffer as u8);
+    }
+    bytes
+}
@@ -0,0 +1,66 @@
+use std::str::FromStr;
+
+use sfml::window::mouse;
+
+/// How the player moves their paddle and shoots
+#[derive(Clone, Copy, PartialEq)]
+pub enum Control {
+    // Up and down, space to shoot
+    Keyboard,
+    // The paddle speeds up towards the cursor like it does for the arrow keys
+    Mouse,
+    // The paddle sits wherever the cursor is
+    MouseDirect,
+    // The paddle's window is dragged around like any other window
+    Drag,
+}
+
+impl FromStr for Control {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "keyboard" => Ok(Control::Keyboard),
+            "mouse" => Ok(Control::Mouse),
+            "mouse_direct" => Ok(Control::MouseDirect),
+            "drag" => Ok(Control::Drag),
+            _ => Err(format!(
+                "unknown control `{}` (expected keyboard, mouse, mouse_direct or drag)",
+                value
+            )),
+        }
+    }
+}
+
+/// Watches the left mouse button anywhere on the desktop, since the cursor is rarely over the
+/// paddle's window and clicks elsewhere never reach the game
+#[derive(Default)]
+pub struct MouseButton {
+    pressed: bool,
+}
+
+impl MouseButton {
+    /// `Some(true)` when the button went down since the last call, `Some(false)` when it went up
+    pub fn update(&mut self) -> Option<bool> {
+        let pressed = mouse::Button::Left.is_pressed();
+        if pressed == self.pressed {
+            return None;
+        }
+        self.pressed = pressed;
+        Some(pressed)
+    }
+}
+
+// How far the cursor can be from the paddle's middle before it moves towards it
+const FOLLOW_DEAD_ZONE: f32 = 20.0;
+
+/// Up or down input that steers a paddle at `paddle_y` towards the cursor at `cursor_y`
+pub fn follow(paddle_y: f32, cursor_y: f32) -> i8 {
+    if cursor_y < paddle_y - FOLLOW_DEAD_ZONE {
+        -1
+    } else if cursor_y > paddle_y + FOLLOW_DEAD_ZONE {
+        1
+    } else {
+        0
+    }
+}
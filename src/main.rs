@@ -1,456 +1,400 @@
+mod ai;
+mod capture;
+mod cli;
+mod config;
+mod effects;
 mod entity;
+mod error;
+mod four_player;
+mod game;
+mod gif;
+mod input;
+mod modes;
+mod motion;
+mod png;
+mod records;
+mod render;
+mod replay;
+mod scoreboard;
+mod serve;
+mod session;
+mod shot;
+mod sizing;
+#[cfg(test)]
+mod snapshot;
+mod stats;
+mod theme;
 mod utils;
 
-use std::time::{Duration, Instant};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+};
 
-use once_cell::sync::Lazy;
 use sfml::{
-    graphics::{Color, Font, RenderTarget, Text, Transformable},
-    system::Vector2,
-    window::{Event, Key, VideoMode},
+    graphics::{Color, Font},
+    system::{Vector2, Vector2u},
+    window::VideoMode,
 };
 
-use entity::Entity;
-
-use utils::normalize_vector;
-
-static SCREEN_WIDTH: Lazy<u32> = Lazy::new(|| VideoMode::desktop_mode().width);
-static SCREEN_HEIGHT: Lazy<u32> = Lazy::new(|| VideoMode::desktop_mode().height);
-
-const FONT_DATA: &'static [u8] = include_bytes!("../assets/Roboto-Regular.ttf");
-
-fn main() {
-    let mut playing: bool = false;
-
-    const MAX_PLAYER_SPEED: f32 = 15.0;
-    let mut max_ball_speed: f32 = 15.0;
-    const INIT_BALL_SPEED: f32 = 20.0;
-
-    let mut up_pressed: bool = false;
-    let mut down_pressed: bool = false;
-
-    const PLAYER_WINDOW_WIDTH: u32 = 75;
-    const PLAYER_WINDOW_HEIGHT: u32 = 300;
-
-    const BALL_SIDE: u32 = 100;
-
-    let ball_center_pos: Vector2<f32> =
-        Vector2::new((*SCREEN_WIDTH / 2) as f32, (*SCREEN_HEIGHT / 2) as f32);
-
-    let mut input: i8;
-
-    let mut shoot_timer: u8 = 0;
-    const MAX_SHOOT_TIMER: u8 = 10;
-    let mut shoot_buffer: u8 = 0;
-    const MAX_SHOOT_BUFFER: u8 = 10;
-
-    let mut score: (u8, u8) = (0, 0);
-
-    const IMPACT_SCALE: f32 = 1.1;
-    const SCORE_IMPACT_FORCE: f32 = 30.0;
-
-    // Ball setup
-
-    let mut ball: Entity = Entity::new(
-        ball_center_pos,
-        BALL_SIDE,
-        BALL_SIDE,
-        String::from("Ball"),
-        Color::WHITE,
-        1.0,
-        1.0,
-    );
-
-    // Score window setup
-
-    let mut score_board = Entity::new(
-        Vector2::new((*SCREEN_WIDTH / 2) as f32, 150.0),
-        250,
-        100,
-        String::from("Score"),
-        Color::WHITE,
-        0.5,
-        0.85,
-    );
-
-    // Player setup
-
-    let mut player: Entity = Entity::new(
-        Vector2::new(
-            (PLAYER_WINDOW_WIDTH + (PLAYER_WINDOW_WIDTH * 1)) as f32,
-            (*SCREEN_HEIGHT / 2) as f32,
-        ),
-        PLAYER_WINDOW_WIDTH,
-        PLAYER_WINDOW_HEIGHT,
-        String::from("Player"),
-        Color::BLUE,
-        0.75,
-        0.75,
-    );
-
-    // AI setup
-
-    let mut ai: Entity = Entity::new(
-        Vector2::new(
-            (*SCREEN_WIDTH - PLAYER_WINDOW_WIDTH - (PLAYER_WINDOW_WIDTH * 1)) as f32,
-            (*SCREEN_HEIGHT / 2) as f32,
-        ),
-        PLAYER_WINDOW_WIDTH,
-        PLAYER_WINDOW_HEIGHT,
-        String::from("AI"),
-        Color::RED,
-        0.75,
-        0.75,
-    );
-
-    let font = unsafe { Font::from_memory(FONT_DATA).unwrap() };
-    let score_string = format!("{} - {}", score.0, score.1);
-    let mut score_text = Text::new(&score_string, &font, 75);
-    score_text.set_fill_color(Color::BLACK);
-    let text_rect = score_text.local_bounds();
-    score_text.set_origin(Vector2::new(text_rect.width / 2.0, text_rect.height / 1.2));
-    score_text.set_position(Vector2::new(
-        score_board.half_size.x as f32,
-        score_board.half_size.y as f32,
-    ));
-
-    // Game loop
-
-    let mut last_update = Instant::now();
-    let frame_duration = Duration::from_secs_f64(1.0 / 60.0); // 1/60th of a second
-    let mut delay_multiplier: u32 = 1;
-
-    while player.window.is_open() {
-        // Event handling
-        while let Some(evt) = player.window.poll_event() {
-            match evt {
-                Event::Closed => player.window.close(),
-                Event::KeyPressed { code, .. } => match code {
-                    Key::Up => {
-                        up_pressed = true;
-                    }
-                    Key::Down => {
-                        down_pressed = true;
-                    }
-                    Key::Escape => {
-                        player.window.close();
-                    }
-                    Key::Space => {
-                        if !playing {
-                            println!("Starting the game!");
-                            ball.velocity.x = INIT_BALL_SPEED;
-                            playing = true;
-                        } else {
-                            if shoot_timer > 0 {
-                                shoot_timer = 0;
-                                ball.velocity.x = max_ball_speed * ball.velocity.x.signum();
-                                ball.velocity.y = 0.0;
-                                ball.color = Color::YELLOW;
-                                delay_multiplier = 5;
-                                player.color = Color::YELLOW;
-                                player.set_display_scale(IMPACT_SCALE)
-                            } else {
-                                shoot_buffer = MAX_SHOOT_BUFFER;
-                            }
-                        }
-                    }
-                    _ => {}
-                },
-                Event::KeyReleased { code, .. } => match code {
-                    Key::Up => {
-                        up_pressed = false;
-                    }
-                    Key::Down => {
-                        down_pressed = false;
-                    }
-                    _ => {}
-                },
-                _ => {}
-            }
-        }
-
-        // Input handling
-
-        input = 0;
-
-        if up_pressed {
-            input -= 1;
-        }
-        if down_pressed {
-            input += 1;
-        }
-
-        // Player Logic
-
-        if input == 0 {
-            // Decelerating
-            let velocity_sign = player.velocity.y.signum();
-            player.acceleration.y = -1.0 * velocity_sign;
-            player.velocity.y += player.acceleration.y;
-            player.velocity.y =
-                player.velocity.y.abs().clamp(0.0, MAX_PLAYER_SPEED) * velocity_sign;
-            if player.velocity.y.abs() < 2.5 {
-                player.velocity.y = 0.0;
+use capture::{Capture, CaptureRenderer};
+use cli::{Command, PlayOptions};
+use config::Config;
+use error::GameError;
+use four_player::{Edge, Seat};
+use game::GameOptions;
+use input::Control;
+use modes::Mode;
+use records::{RecordWatch, Records};
+use render::{
+    single_window::SingleWindowRenderer, windows::WindowRenderer, NullRenderer, Renderer,
+};
+use replay::ReplayRecorder;
+use serve::Side;
+use stats::MatchStats;
+
+const FONT_DATA: &[u8] = include_bytes!("../assets/Roboto-Regular.ttf");
+
+/// The font file at `path`, or the built-in font if there's none or it can't be used
+fn load_font(path: Option<&Path>) -> &'static [u8] {
+    let Some(path) = path else {
+        return FONT_DATA;
+    };
+
+    match fs::read(path) {
+        Ok(data) => {
+            // The renderers hold on to the font until the game closes
+            let data: &'static [u8] = Box::leak(data.into_boxed_slice());
+            if unsafe { Font::from_memory(data) }.is_some() {
+                data
+            } else {
+                println!("{} isn't a font, using the built-in one", path.display());
+                FONT_DATA
             }
-        } else {
-            // Accelerating
-            player.acceleration.y = input as f32 * 1.0;
-            player.velocity.y += player.acceleration.y;
         }
-
-        player.r#move();
-
-        if player.position.y < 48.0 + player.half_size.y as f32
-            || player.position.y > *SCREEN_HEIGHT as f32 - player.half_size.y as f32
-        {
-            player.velocity.y = 0.0;
-
-            let clamped_player_pos_y: f32 = player.position.y.clamp(
-                (48 + player.half_size.y) as f32,
-                (*SCREEN_HEIGHT - player.half_size.y) as f32,
+        Err(err) => {
+            println!(
+                "Couldn't read the font {}: {}, using the built-in one",
+                path.display(),
+                err
             );
-
-            player.set_position(None, Some(clamped_player_pos_y));
+            FONT_DATA
         }
+    }
+}
 
-        // AI Logic
-
-        if ball.velocity.x > 0.0 {
-            let ball_overlap = Entity::get_overlap(&ai, &ball);
-            if ball_overlap.y <= ball.size.y as i32 {
-                if ball.position.y < ai.position.y {
-                    ai.acceleration.y = -1.0 * 1.0;
-                } else {
-                    ai.acceleration.y = 1.0;
-                }
-                ai.velocity.y += ai.acceleration.y;
-            } else {
-                let velocity_sign = ai.velocity.y.signum();
-                ai.acceleration.y = -1.0 * velocity_sign;
-                ai.velocity.y += ai.acceleration.y;
-                ai.velocity.y = ai.velocity.y.abs().clamp(0.0, MAX_PLAYER_SPEED) * velocity_sign;
-                if ai.velocity.y.abs() < 2.5 {
-                    ai.velocity.y = 0.0;
-                }
-            }
-        }
+/// SFML aborts the whole game when there's no X display to connect to, so look first
+fn check_display() -> Result<(), GameError> {
+    if cfg!(target_os = "linux") && env::var_os("DISPLAY").is_none() {
+        return Err(GameError::NoDisplay);
+    }
+    Ok(())
+}
 
-        if ai.position.y < 48.0 + ai.half_size.y as f32
-            || ai.position.y > *SCREEN_HEIGHT as f32 - ai.half_size.y as f32
-        {
-            ai.velocity.y = 0.0;
+/// Reads the config named on the command line, or the usual one
+fn load_config(options: &PlayOptions) -> Result<Config, GameError> {
+    let mut config = match &options.config {
+        Some(path) => Config::load_file(path).map_err(GameError::Config)?,
+        None => Config::load().map_err(GameError::Config)?,
+    };
 
-            let clamped_ai_pos_y: f32 = ai.position.y.clamp(
-                (48 + ai.half_size.y) as f32,
-                (*SCREEN_HEIGHT - ai.half_size.y) as f32,
-            );
+    if let Some(difficulty) = options.difficulty {
+        config.ai_difficulty = difficulty;
+    }
+    if let Some(name) = &options.profile {
+        config.score_board_layout.player_name = name.clone();
+    }
+    Ok(config)
+}
 
-            ai.set_position(None, Some(clamped_ai_pos_y));
-        }
+/// Opens the windows to draw a desktop of `desktop_size` in, with keys going to `input_window`
+fn open_renderer(
+    font_data: &'static [u8],
+    desktop_size: Vector2u,
+    input_window: &str,
+    backdrop: Color,
+    options: &PlayOptions,
+) -> Result<Box<dyn Renderer>, GameError> {
+    // Some window managers won't let windows be moved, so the scene can be drawn in one instead
+    let renderer: Box<dyn Renderer> = if options.single_window || options.fullscreen {
+        Box::new(SingleWindowRenderer::new(
+            font_data,
+            desktop_size,
+            options.fullscreen,
+            backdrop,
+        )?)
+    } else {
+        Box::new(WindowRenderer::new(font_data, input_window)?)
+    };
+    Ok(renderer)
+}
 
-        ai.r#move();
-
-        // Ball Logic
-
-        if playing {
-            ball.r#move();
-
-            let other_entities = [&mut player, &mut ai, &mut score_board];
-
-            // Ball collision
-
-            for entity in other_entities {
-                let ball_player_overlap: Vector2<i32> = Entity::get_overlap(&ball, entity);
-                if ball_player_overlap.x > 0 && ball_player_overlap.y > 0 {
-                    println!("Ball collided with {0}!", entity.name);
-
-                    let prev_overlap: Vector2<i32> = Entity::get_prev_overlap(&ball, entity);
-                    let adjustment_sign: Vector2<f32> = Vector2::new(
-                        if entity.position.x > ball.position.x {
-                            1.0
-                        } else {
-                            -1.0
-                        },
-                        if entity.position.y < ball.position.y {
-                            1.0
-                        } else {
-                            -1.0
-                        },
-                    );
-                    if prev_overlap.y > 0 {
-                        ball.velocity.x *= -1.0;
-                        let adjustment: f32 =
-                            ball.position.x - (ball_player_overlap.x as f32 * adjustment_sign.x);
-                        ball.set_position(Some(adjustment), None);
-                    } else if prev_overlap.x > 0 {
-                        ball.velocity.y *= -1.0;
-                        let adjustment: f32 =
-                            ball.position.y + (ball_player_overlap.y as f32 * adjustment_sign.y);
-                        ball.set_position(None, Some(adjustment));
-                    } else {
-                        if ball_player_overlap.y >= ball_player_overlap.x {
-                            ball.velocity.x *= -1.0;
-                            let adjustment = ball.position.x
-                                - (ball_player_overlap.x as f32 * adjustment_sign.x);
-                            ball.set_position(Some(adjustment), None);
-                        } else {
-                            ball.velocity.y *= -1.0;
-                            let adjustment = ball.position.y
-                                - (ball_player_overlap.y as f32 * adjustment_sign.y);
-                            ball.set_position(None, Some(adjustment));
-                        }
-                    }
-
-                    ball.velocity += entity.velocity;
-                    ball.velocity = normalize_vector(ball.velocity) * max_ball_speed;
-
-                    let mut impact_force = Vector2::new(ball.velocity.x, ball.velocity.y);
-
-                    if entity.name == "Player" {
-                        if shoot_buffer > 0 {
-                            shoot_buffer = 0;
-                            ball.velocity.x = max_ball_speed * ball.velocity.x.signum();
-                            ball.velocity.y = 0.0;
-                            ball.color = Color::YELLOW;
-                            impact_force *= 2.0;
-                            delay_multiplier = 5;
-                        } else {
-                            shoot_timer = MAX_SHOOT_TIMER;
-                            ball.color = Color::WHITE;
-
-                            delay_multiplier = 3;
-                        }
-                    } else {
-                        ball.color = Color::WHITE;
-                        delay_multiplier = 3;
-                    }
-                    entity.color = ball.color;
-
-                    entity.impact(&impact_force);
-                }
-            }
+fn play(options: PlayOptions) -> Result<(), GameError> {
+    let mut config = load_config(&options)?;
+    if let Some(seed) = options.seed {
+        utils::seed_rng(seed);
+    }
 
-            if (ball.position.y - ball.half_size.y as f32) < 48.0
-                || ball.position.y + ball.half_size.y as f32 > *SCREEN_HEIGHT as f32
-            {
-                println!("Ball collided with the edge of the screen!");
-                ball.velocity.y *= -1.0;
-                let clamped_ball_pos_y: f32 = ball.position.y.clamp(
-                    (48 + ball.half_size.y) as f32,
-                    (*SCREEN_HEIGHT - ball.half_size.y) as f32,
-                );
-                ball.set_position(None, Some(clamped_ball_pos_y));
-            }
+    check_display()?;
+    let arena = options.arena.unwrap_or_else(|| {
+        let desktop = VideoMode::desktop_mode();
+        Vector2::new(desktop.width, desktop.height)
+    });
+
+    let font_data = load_font(config.score_board_layout.font.as_deref());
+    // Measures the score text to fit the window around it
+    let font = unsafe { Font::from_memory(font_data) }
+        .ok_or_else(|| GameError::Font(String::from("the score board font")))?;
+
+    let mode = options.mode.unwrap_or_default();
+    let input_window = match mode {
+        Mode::FourPlayer => four_player::input_window(&config.seats),
+        _ => "Player",
+    };
+    let mut renderer = open_renderer(
+        font_data,
+        arena,
+        input_window,
+        config.theme.backdrop,
+        &options,
+    )?;
+    if config.control == Control::Drag && (options.single_window || options.fullscreen) {
+        println!("There's no paddle window to drag in a single window, using the keyboard");
+        config.control = Control::Keyboard;
+    }
 
-            if (ball.position.x - ball.half_size.x as f32) < 0.0
-                || ball.position.x + ball.half_size.x as f32 > *SCREEN_WIDTH as f32
-            {
-                println!("Point!");
-
-                if (ball.position.x - ball.half_size.x as f32) < 0.0 {
-                    score.1 += 1;
-                } else {
-                    score.0 += 1;
-                }
-                let score_string = format!("{} - {}", score.0, score.1);
-                score_text.set_string(&score_string);
-                let text_rect = score_text.local_bounds();
-                score_text.set_origin(Vector2::new(text_rect.width / 2.0, text_rect.height / 1.2));
-
-                player.set_scale(1.0);
-                player.set_position(None, Some((*SCREEN_HEIGHT / 2) as f32 - 24.0));
-
-                let ball_player_delta = ball.position - player.position;
-                player.impact(
-                    &((Vector2::new(1.0, 1.0) - (ball_player_delta / *SCREEN_WIDTH as f32))
-                        * SCORE_IMPACT_FORCE),
-                );
-
-                ai.velocity.y = 0.0;
-                ai.set_position(None, Some((*SCREEN_HEIGHT / 2) as f32 - 24.0));
-                ai.set_scale(1.0);
-                let ball_ai_delta = ball.position - ai.position;
-                ai.impact(
-                    &((Vector2::new(1.0, 1.0) - (ball_ai_delta / *SCREEN_WIDTH as f32))
-                        * SCORE_IMPACT_FORCE),
-                );
-
-                playing = false;
-                ball.velocity.x = 0.0;
-                ball.velocity.y = 0.0;
-                ball.set_position(Some(ball_center_pos.x), Some(ball_center_pos.y));
-                max_ball_speed = INIT_BALL_SPEED;
-                ball.color = Color::WHITE;
-
-                delay_multiplier = 30;
-            }
+    if let Some(path) = &options.record {
+        match replay::create(path, arena) {
+            Ok(output) => renderer = Box::new(ReplayRecorder::new(renderer, output)),
+            Err(err) => println!(
+                "Couldn't record to {}: {}, not recording the match",
+                path.display(),
+                err
+            ),
         }
+    }
 
-        ball.window.clear(ball.color);
-        ball.window.display();
-
-        // Display player
-        player.update_impact();
-        player.draw(&score_board, &ball);
+    if let Some(path) = &options.capture {
+        let capture =
+            capture::parse_size(options.capture_size.as_deref(), arena).and_then(|image_size| {
+                Capture::new(path, arena, image_size, config.theme.backdrop)
+                    .map_err(|err| format!("Couldn't capture to {}: {}", path.display(), err))
+            });
+        match capture {
+            Ok(capture) => renderer = Box::new(CaptureRenderer::new(renderer, capture)),
+            Err(err) => println!("{}, not capturing the match", err),
+        }
+    }
 
-        // Display AI
-        ai.update_impact();
-        ai.draw(&score_board, &ball);
+    let game_options = GameOptions {
+        arena,
+        autopilot: false,
+        realtime: true,
+        matches: None,
+        log_events: true,
+    };
+
+    let mut game_mode = match mode {
+        // Four player matches have a game loop of their own, and no stats or records
+        Mode::FourPlayer => {
+            return four_player::run(&config, renderer.as_mut(), Some(&font), &game_options)
+                .map(|_| ())
+        }
+        _ => modes::create(mode, &config)?,
+    };
+
+    let name = &config.score_board_layout.player_name;
+    let (mut match_stats, mut record_watch) = if game_mode.keeps_stats() {
+        // Records are kept per game mode
+        (
+            MatchStats::load(name, config.ai_difficulty),
+            RecordWatch::new(game_mode.name(), name),
+        )
+    } else {
+        (
+            MatchStats::unsaved(name, config.ai_difficulty),
+            RecordWatch::disabled(),
+        )
+    };
+
+    let result = game::run(
+        &config,
+        renderer.as_mut(),
+        Some(&font),
+        &mut match_stats,
+        &mut record_watch,
+        game_mode.as_mut(),
+        &game_options,
+    );
 
-        // Score window logic
+    // Keep rallies and smashes from a match that was left unfinished
+    match_stats.save();
+    result.map(|_| ())
+}
 
-        score_board.update_impact();
+/// Plays AI against AI without drawing anything or saving stats and records
+fn simulate(options: PlayOptions, matches: u32) -> Result<(), GameError> {
+    let config = load_config(&options)?;
+    let mode = options.mode.unwrap_or_default();
+    if config.points_to_win == 0 && config.lives == 0 && mode != Mode::TimeAttack {
+        return Err(GameError::Config(String::from(
+            "Simulated matches need to end, set `points_to_win` or `lives` above 0",
+        )));
+    }
+    if let Some(seed) = options.seed {
+        utils::seed_rng(seed);
+    }
 
-        score_board.window.clear(score_board.color);
+    let game_options = GameOptions {
+        // No desktop to measure, so the usual 1080p
+        arena: options.arena.unwrap_or(Vector2::new(1920, 1080)),
+        autopilot: true,
+        realtime: false,
+        matches: Some(matches),
+        log_events: false,
+    };
+
+    let mut game_mode = match mode {
+        Mode::FourPlayer => return simulate_four_player(&config, &game_options),
+        _ => modes::create(mode, &config)?,
+    };
+
+    let layout = &config.score_board_layout;
+    let results = game::run(
+        &config,
+        &mut NullRenderer,
+        None,
+        &mut MatchStats::unsaved(&layout.player_name, config.ai_difficulty),
+        &mut RecordWatch::disabled(),
+        game_mode.as_mut(),
+        &game_options,
+    )?;
+
+    for (index, result) in results.iter().enumerate() {
+        println!(
+            "Match {}: {} {} - {} {}",
+            index + 1,
+            layout.player_name,
+            result.score.0,
+            result.score.1,
+            layout.ai_name
+        );
+    }
+    let wins = results
+        .iter()
+        .filter(|result| result.winner == Side::Player)
+        .count();
+    println!(
+        "{} won {} of {} against {} AI",
+        layout.player_name,
+        wins,
+        results.len(),
+        config.ai_difficulty.name()
+    );
+    Ok(())
+}
 
-        score_board.window.draw(&mut score_text);
+/// Plays four player matches AI against AI, printing everyone's score
+fn simulate_four_player(config: &Config, game_options: &GameOptions) -> Result<(), GameError> {
+    let results = four_player::run(config, &mut NullRenderer, None, game_options)?;
+    let seated: Vec<Edge> = Edge::ALL
+        .into_iter()
+        .zip(config.seats)
+        .filter(|&(_, seat)| seat != Seat::Empty)
+        .map(|(edge, _)| edge)
+        .collect();
+
+    for (index, result) in results.iter().enumerate() {
+        let scores: Vec<String> = seated
+            .iter()
+            .map(|&edge| format!("{} {}", edge.name(), result.scores[edge as usize]))
+            .collect();
+        println!("Match {}: {}", index + 1, scores.join(", "));
+    }
+    for edge in seated {
+        let wins = results
+            .iter()
+            .filter(|result| result.winner == edge)
+            .count();
+        println!("{} won {} of {}", edge.name(), wins, results.len());
+    }
+    Ok(())
+}
 
-        score_board.window.display();
+/// Watches a replay, or exports it without opening any window
+fn replay(path: &Path, export: Option<&Path>, options: PlayOptions) -> Result<(), GameError> {
+    let config = load_config(&options)?;
+    if let Some(output) = export {
+        return capture::export(
+            path,
+            output,
+            options.capture_size.as_deref(),
+            config.theme.backdrop,
+        )
+        .map_err(GameError::File);
+    }
 
-        score_board.set_display_scale(1.0);
+    let replay = replay::load(path)
+        .map_err(|err| GameError::File(format!("Couldn't read {}: {}", path.display(), err)))?;
+    let font_data = load_font(config.score_board_layout.font.as_deref());
+    check_display()?;
+    let mut renderer = open_renderer(
+        font_data,
+        replay.desktop_size,
+        "Player",
+        config.theme.backdrop,
+        &options,
+    )?;
+
+    replay::play(&replay, renderer.as_mut())
+}
 
-        // Focus on player window
-        player.window.request_focus();
+fn validate_config(path: Option<PathBuf>) -> Result<(), GameError> {
+    let path = path
+        .or_else(config::config_path)
+        .ok_or_else(|| GameError::Config(String::from("No config file to check, name one")))?;
+    Config::load_file(&path).map_err(GameError::Config)?;
+    println!("{} is valid", path.display());
+    Ok(())
+}
 
-        // Timers
-        if shoot_buffer > 0 {
-            shoot_buffer -= 1;
+fn main() {
+    let command = match cli::parse(env::args().skip(1).collect()) {
+        Ok(command) => command,
+        Err(err) => {
+            eprintln!("{}\nRun `window-pong --help` for usage", err);
+            process::exit(2);
         }
-        if shoot_timer > 0 {
-            shoot_timer -= 1;
+    };
+
+    let result = match command {
+        Command::Play(options) => play(options),
+        Command::Simulate { options, matches } => simulate(options, matches),
+        Command::Replay {
+            path,
+            export,
+            options,
+        } => replay(&path, export.as_deref(), options),
+        Command::Stats { name } => stats::print_stats(name.as_deref())
+            .map_err(|err| GameError::File(format!("Couldn't read the stats: {}", err))),
+        Command::Records => {
+            Records::load().print();
+            Ok(())
         }
-
-        player.update_eye_timers();
-        ai.update_eye_timers();
-
-        if playing {
-            max_ball_speed += 0.005;
-            player.set_scale_xy(
-                None,
-                Some((player.scale.y - 0.0001).clamp(0.25, IMPACT_SCALE)),
-            );
-            ai.set_scale_xy(None, Some((ai.scale.y - 0.0001).clamp(0.25, IMPACT_SCALE)));
+        Command::ValidateConfig { path } => validate_config(path),
+        Command::Help => {
+            println!("{}", cli::USAGE);
+            Ok(())
         }
-
-        // Wait for next frame
-        if let Some(sleep_duration) = (frame_duration * delay_multiplier)
-            .checked_sub(Instant::now().duration_since(last_update))
-        {
-            std::thread::sleep(sleep_duration);
+        Command::Version => {
+            println!("window-pong {}", env!("CARGO_PKG_VERSION"));
+            Ok(())
         }
-        if delay_multiplier > 1 {
-            delay_multiplier = 1;
+    };
 
-            if player.color != Color::BLUE {
-                player.color = Color::BLUE;
-            }
-
-            if ai.color != Color::RED {
-                ai.color = Color::RED;
-            }
-
-            score_board.color = Color::WHITE;
-        }
-        last_update = Instant::now();
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        process::exit(err.exit_code());
     }
 }
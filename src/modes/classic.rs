@@ -0,0 +1,141 @@
+//! First to a number of points, with the ball speeding up and the paddles resizing as it goes
+
+use std::cmp::Ordering;
+
+use super::{GameMode, MatchState};
+use crate::{config::Config, entity::Entity, serve::Side, sizing::PaddleSizing};
+
+// How much faster the ball comes off the paddles for every frame it's in play
+const BALL_SPEEDUP: f32 = 0.005;
+
+pub struct Classic {
+    // Points that win the match, 0 to keep going
+    points_to_win: u8,
+    paddle_sizing: PaddleSizing,
+}
+
+impl Classic {
+    pub fn new(config: &Config) -> Classic {
+        // Taking all of the other side's lives is the same as scoring that many points
+        let points_to_win = if config.lives > 0 {
+            config.lives
+        } else {
+            config.points_to_win
+        };
+        Classic::first_to(config, points_to_win)
+    }
+
+    /// The classic rules played to some other number of points, 0 to only end another way
+    pub fn first_to(config: &Config, points_to_win: u8) -> Classic {
+        Classic {
+            points_to_win,
+            paddle_sizing: config.paddle_sizing.clone(),
+        }
+    }
+}
+
+/// Whoever is ahead, if anyone
+pub fn leader(score: (u8, u8)) -> Option<Side> {
+    match score.0.cmp(&score.1) {
+        Ordering::Greater => Some(Side::Player),
+        Ordering::Less => Some(Side::Ai),
+        Ordering::Equal => None,
+    }
+}
+
+impl GameMode for Classic {
+    fn name(&self) -> &'static str {
+        "classic"
+    }
+
+    fn on_hit(
+        &mut self,
+        _state: &mut MatchState,
+        _side: Side,
+        paddle: &mut Entity,
+        _ball: &Entity,
+        _smash: bool,
+    ) {
+        self.paddle_sizing.on_return(paddle);
+    }
+
+    fn on_point(&mut self, _state: &mut MatchState, conceded: Side, paddles: [&mut Entity; 2]) {
+        let [player, ai] = paddles;
+        self.paddle_sizing
+            .on_point(player, conceded == Side::Player);
+        self.paddle_sizing.on_point(ai, conceded == Side::Ai);
+    }
+
+    fn on_tick(
+        &mut self,
+        state: &mut MatchState,
+        playing: bool,
+        _frames: u32,
+        paddles: [&mut Entity; 2],
+    ) {
+        if playing {
+            state.ball_speed += BALL_SPEEDUP;
+            for paddle in paddles {
+                self.paddle_sizing.on_tick(paddle);
+            }
+        }
+    }
+
+    fn is_match_over(&self, state: &MatchState) -> Option<Side> {
+        let score = state.score;
+        if self.points_to_win == 0 || score.0.max(score.1) < self.points_to_win {
+            return None;
+        }
+        leader(score)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sfml::system::Vector2;
+
+    use super::{Classic, GameMode, MatchState};
+    use crate::{config::Config, serve::Side};
+
+    fn state(score: (u8, u8)) -> MatchState {
+        MatchState {
+            score,
+            ball_speed: 10.0,
+            frames: 0,
+            balls: 1,
+            new_balls: Vec::new(),
+            arena: Vector2::new(1920, 1080),
+        }
+    }
+
+    #[test]
+    fn first_to_the_points_wins() {
+        let classic = Classic::new(&Config::default());
+        assert!(classic.is_match_over(&state((10, 10))).is_none());
+        assert!(classic.is_match_over(&state((11, 9))) == Some(Side::Player));
+        assert!(classic.is_match_over(&state((4, 11))) == Some(Side::Ai));
+    }
+
+    #[test]
+    fn lives_take_over_from_points() {
+        let config = Config {
+            lives: 3,
+            ..Config::default()
+        };
+        let classic = Classic::new(&config);
+        assert!(classic.is_match_over(&state((2, 2))).is_none());
+        assert!(classic.is_match_over(&state((3, 1))) == Some(Side::Player));
+        assert!(classic.is_match_over(&state((0, 3))) == Some(Side::Ai));
+    }
+
+    #[test]
+    fn no_points_to_win_never_ends() {
+        let config = Config {
+            points_to_win: 0,
+            ..Config::default()
+        };
+        assert!(Classic::new(&config)
+            .is_match_over(&state((200, 0)))
+            .is_none());
+    }
+}
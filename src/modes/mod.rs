@@ -0,0 +1,156 @@
+//! The rules of each two paddle game mode, hooked into the match as it's played
+
+pub mod classic;
+pub mod multiball;
+pub mod practice;
+pub mod time_attack;
+
+use std::str::FromStr;
+
+use sfml::system::{Vector2f, Vector2u};
+
+use crate::{config::Config, entity::Entity, error::GameError, serve::Side};
+use classic::Classic;
+use multiball::Multiball;
+use practice::Practice;
+use time_attack::TimeAttack;
+
+/// Game modes that can be picked on the command line
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum Mode {
+    #[default]
+    Classic,
+    FourPlayer,
+    Multiball,
+    Practice,
+    TimeAttack,
+}
+
+impl FromStr for Mode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "classic" => Ok(Mode::Classic),
+            "four_player" => Ok(Mode::FourPlayer),
+            "multiball" => Ok(Mode::Multiball),
+            "practice" => Ok(Mode::Practice),
+            "time_attack" => Ok(Mode::TimeAttack),
+            _ => Err(format!(
+                "unknown game mode `{}` (expected classic, four_player, multiball, practice or time_attack)",
+                value
+            )),
+        }
+    }
+}
+
+/// The rules for a two paddle mode. Four player matches have a game loop of their own, so
+/// there are none for them here
+pub fn create(mode: Mode, config: &Config) -> Result<Box<dyn GameMode>, GameError> {
+    match mode {
+        Mode::Classic => Ok(Box::new(Classic::new(config))),
+        Mode::Multiball => Ok(Box::new(Multiball::new(config))),
+        Mode::Practice => Ok(Box::new(Practice::new(config))),
+        Mode::TimeAttack => Ok(Box::new(TimeAttack::new(config))),
+        Mode::FourPlayer => Err(GameError::Config(String::from(
+            "four player matches aren't played on the two paddle rules",
+        ))),
+    }
+}
+
+/// The match as the rules see it
+pub struct MatchState {
+    pub score: (u8, u8),
+    // Speed the ball comes off a paddle at, rising as the point goes on
+    pub ball_speed: f32,
+    // Frames played since the first serve of the match, not counting pauses
+    pub frames: u32,
+    // Balls in play, or waiting to be served
+    pub balls: usize,
+    // Balls to put into play at the end of the frame, as a position and velocity
+    pub new_balls: Vec<(Vector2f, Vector2f)>,
+    pub arena: Vector2u,
+}
+
+/// What happens to a ball reaching the edge behind a paddle
+#[derive(Clone, Copy, PartialEq)]
+pub enum Wall {
+    // It comes back, there's a wall there
+    Bounce,
+    // The side whose edge it is concedes a point
+    Point,
+    // It's out of play without anyone scoring
+    Out,
+}
+
+/// Rules for a match, called from the game loop as things happen. Everything but the end of
+/// the match has a default that leaves it to the game loop
+pub trait GameMode {
+    /// The name it's picked with, records are kept under it
+    fn name(&self) -> &'static str;
+
+    /// Called every frame the ball waits to be served. Modes that serve it themselves return
+    /// whether it's been launched, the rest leave it to whoever's turn it is
+    fn on_serve(&mut self, _state: &mut MatchState, _ball: &mut Entity) -> Option<bool> {
+        None
+    }
+
+    /// The player pressed shoot while the ball was in play
+    fn on_shot(&mut self) {}
+
+    /// A paddle returned a ball, which has already bounced off it
+    fn on_hit(
+        &mut self,
+        _state: &mut MatchState,
+        _side: Side,
+        _paddle: &mut Entity,
+        _ball: &Entity,
+        _smash: bool,
+    ) {
+    }
+
+    /// A shot just after the ball left the paddle turned the return into a smash
+    fn on_late_smash(&mut self, _side: Side) {}
+
+    /// A ball reached the edge behind the paddle on `side`
+    fn on_wall(&mut self, _state: &mut MatchState, _side: Side) -> Wall {
+        Wall::Point
+    }
+
+    /// `conceded` let a point in, the score already counts it. A player missing a ball that's
+    /// out without a point, like in practice, counts as conceding too
+    fn on_point(&mut self, _state: &mut MatchState, _conceded: Side, _paddles: [&mut Entity; 2]) {}
+
+    /// Called every frame the match isn't paused, with frames held longer counting as several
+    fn on_tick(
+        &mut self,
+        _state: &mut MatchState,
+        _playing: bool,
+        _frames: u32,
+        _paddles: [&mut Entity; 2],
+    ) {
+    }
+
+    /// Whoever won, once the match is over
+    fn is_match_over(&self, state: &MatchState) -> Option<Side>;
+
+    /// The score board's text, in place of the layout
+    fn score_text(&self, _state: &MatchState) -> Option<String> {
+        None
+    }
+
+    /// A line under the score, like the time left
+    fn clock(&self, _state: &MatchState) -> Option<String> {
+        None
+    }
+
+    /// Whether there's an AI to play against
+    fn has_opponent(&self) -> bool {
+        true
+    }
+
+    /// Whether matches count towards stats and records
+    fn keeps_stats(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,159 @@
+//! The classic rules, but a long enough rally brings in another ball, sent from the middle
+//! back at whoever just hit
+
+use rand::Rng;
+use sfml::system::Vector2;
+
+use super::{classic::Classic, GameMode, MatchState};
+use crate::{config::Config, entity::Entity, serve::Side, utils};
+
+// Furthest a new ball heads from straight across, in degrees
+const MAX_NEW_BALL_ANGLE: f32 = 30.0;
+
+pub struct Multiball {
+    classic: Classic,
+    // Most balls in play at once
+    max_balls: usize,
+    // Returns between new balls
+    new_ball_every: u32,
+    // Returns since the serve
+    returns: u32,
+}
+
+impl Multiball {
+    pub fn new(config: &Config) -> Multiball {
+        Multiball {
+            classic: Classic::new(config),
+            max_balls: config.max_balls as usize,
+            new_ball_every: config.new_ball_every,
+            returns: 0,
+        }
+    }
+}
+
+impl GameMode for Multiball {
+    fn name(&self) -> &'static str {
+        "multiball"
+    }
+
+    fn on_serve(&mut self, _state: &mut MatchState, _ball: &mut Entity) -> Option<bool> {
+        self.returns = 0;
+        None
+    }
+
+    fn on_hit(
+        &mut self,
+        state: &mut MatchState,
+        side: Side,
+        paddle: &mut Entity,
+        ball: &Entity,
+        smash: bool,
+    ) {
+        self.classic.on_hit(state, side, paddle, ball, smash);
+
+        self.returns += 1;
+        if self.returns.is_multiple_of(self.new_ball_every)
+            && state.balls + state.new_balls.len() < self.max_balls
+        {
+            // Each side has a ball to deal with, rather than two coming at the same paddle
+            let angle = utils::rng()
+                .gen_range(-MAX_NEW_BALL_ANGLE..MAX_NEW_BALL_ANGLE)
+                .to_radians();
+            let toward_hitter = -ball.velocity.x.signum();
+            let middle = Vector2::new(state.arena.x as f32 / 2.0, state.arena.y as f32 / 2.0);
+            let velocity =
+                Vector2::new(toward_hitter * angle.cos(), angle.sin()) * state.ball_speed;
+            state.new_balls.push((middle, velocity));
+        }
+    }
+
+    fn on_point(&mut self, state: &mut MatchState, conceded: Side, paddles: [&mut Entity; 2]) {
+        self.classic.on_point(state, conceded, paddles);
+    }
+
+    fn on_tick(
+        &mut self,
+        state: &mut MatchState,
+        playing: bool,
+        frames: u32,
+        paddles: [&mut Entity; 2],
+    ) {
+        self.classic.on_tick(state, playing, frames, paddles);
+    }
+
+    fn is_match_over(&self, state: &MatchState) -> Option<Side> {
+        self.classic.is_match_over(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sfml::{graphics::Color, system::Vector2};
+
+    use super::{GameMode, MatchState, Multiball};
+    use crate::{config::Config, entity::Entity, serve::Side};
+
+    fn entity(name: &str) -> Entity {
+        Entity::new(
+            Vector2::new(100.0, 100.0),
+            20,
+            20,
+            name.to_string(),
+            Color::WHITE,
+            0.75,
+            0.75,
+        )
+    }
+
+    /// Returns the ball `hits` times in a rally, counting the balls that came in
+    fn rally(multiball: &mut Multiball, state: &mut MatchState, hits: u32) -> usize {
+        let mut paddle = entity("Player");
+        let mut ball = entity("Ball");
+        // Already bounced off the player's paddle, on its way to the AI
+        ball.velocity = Vector2::new(5.0, 0.0);
+        multiball.on_serve(state, &mut ball);
+        for _ in 0..hits {
+            multiball.on_hit(state, Side::Player, &mut paddle, &ball, false);
+        }
+        state.new_balls.len()
+    }
+
+    fn state(balls: usize) -> MatchState {
+        MatchState {
+            score: (0, 0),
+            ball_speed: 10.0,
+            frames: 0,
+            balls,
+            new_balls: Vec::new(),
+            arena: Vector2::new(1920, 1080),
+        }
+    }
+
+    #[test]
+    fn new_ball_every_few_returns() {
+        let mut multiball = Multiball::new(&Config::default());
+        let mut state = state(1);
+        assert_eq!(rally(&mut multiball, &mut state, 3), 0);
+        assert_eq!(rally(&mut multiball, &mut state, 4), 1);
+
+        // From the middle, back at the player who hit it
+        let (position, velocity) = state.new_balls[0];
+        assert_eq!(position, Vector2::new(960.0, 540.0));
+        assert!(velocity.x < 0.0);
+    }
+
+    #[test]
+    fn serving_starts_the_count_again() {
+        let mut multiball = Multiball::new(&Config::default());
+        let mut state = state(1);
+        rally(&mut multiball, &mut state, 3);
+        assert_eq!(rally(&mut multiball, &mut state, 3), 0);
+    }
+
+    #[test]
+    fn no_more_than_the_most_balls() {
+        let mut multiball = Multiball::new(&Config::default());
+        assert_eq!(rally(&mut multiball, &mut state(1), 12), 2);
+        assert_eq!(rally(&mut multiball, &mut state(3), 8), 0);
+    }
+}
@@ -0,0 +1,245 @@
+//! Playing alone against a wall, counting returns and smashes
+
+use std::str::FromStr;
+
+use rand::Rng;
+use sfml::system::{Vector2, Vector2u};
+
+use super::{classic::Classic, GameMode, MatchState, Wall};
+use crate::{
+    config::Config,
+    entity::Entity,
+    serve::Side,
+    utils::{self, vector_length},
+};
+
+/// Where the balls come from in practice mode
+#[derive(Clone, Copy, PartialEq)]
+pub enum Drill {
+    // Serve and keep the ball going against the wall
+    Rally,
+    // A launcher in the wall fires balls at random angles, one at a time
+    Launcher,
+}
+
+impl FromStr for Drill {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "rally" => Ok(Drill::Rally),
+            "launcher" => Ok(Drill::Launcher),
+            _ => Err(format!(
+                "unknown drill `{}` (expected rally or launcher)",
+                value
+            )),
+        }
+    }
+}
+
+// Furthest the launcher fires from straight at the player, in degrees
+const MAX_LAUNCH_ANGLE: f32 = 40.0;
+// Gap between the launched ball and the wall
+const LAUNCH_GAP: f32 = 20.0;
+// The same speed as a serve
+const LAUNCH_SPEED: f32 = 20.0;
+
+pub struct Practice {
+    // The ball still speeds up and the paddle still resizes
+    classic: Classic,
+    drill: Drill,
+    // Returns since the last miss
+    returns: u32,
+    best_returns: u32,
+    top_speed: f32,
+    // Shoot presses while the ball was in play, and how many of them smashed
+    shots: u32,
+    smashes: u32,
+    // Frames until the launcher fires, and how high up the wall it fires from
+    launch_delay: u32,
+    launch_timer: u32,
+    launch_y: f32,
+}
+
+impl Practice {
+    pub fn new(config: &Config) -> Practice {
+        let launch_delay = config.serve_ai_delay;
+        Practice {
+            classic: Classic::first_to(config, 0),
+            drill: config.drill,
+            returns: 0,
+            best_returns: 0,
+            top_speed: 0.0,
+            shots: 0,
+            smashes: 0,
+            launch_delay,
+            launch_timer: launch_delay,
+            launch_y: 0.5,
+        }
+    }
+
+    /// Waits before the launcher fires again, somewhere else along the wall
+    fn reload(&mut self) {
+        self.launch_timer = self.launch_delay;
+        self.launch_y = utils::rng().gen_range(0.15..0.85);
+    }
+
+    /// Holds the ball in the launcher until it's time, returning true once it's been fired
+    fn launch(&mut self, ball: &mut Entity, arena: Vector2u) -> bool {
+        ball.set_position(
+            Some(arena.x as f32 - ball.half_size.x as f32 - LAUNCH_GAP),
+            Some(arena.y as f32 * self.launch_y),
+        );
+        if self.launch_timer > 0 {
+            self.launch_timer -= 1;
+            return false;
+        }
+
+        let angle = utils::rng()
+            .gen_range(-MAX_LAUNCH_ANGLE..MAX_LAUNCH_ANGLE)
+            .to_radians();
+        ball.velocity = Vector2::new(-angle.cos(), angle.sin()) * LAUNCH_SPEED;
+        true
+    }
+
+    fn text(&self) -> String {
+        let accuracy = (self.smashes * 100)
+            .checked_div(self.shots)
+            .map(|percent| format!(" ({}%)", percent))
+            .unwrap_or_default();
+        format!(
+            "Returns {} (best {})\nTop speed {:.1}\nSmashes {} of {}{}",
+            self.returns, self.best_returns, self.top_speed, self.smashes, self.shots, accuracy
+        )
+    }
+}
+
+impl GameMode for Practice {
+    fn name(&self) -> &'static str {
+        "practice"
+    }
+
+    fn on_serve(&mut self, state: &mut MatchState, ball: &mut Entity) -> Option<bool> {
+        match self.drill {
+            Drill::Rally => None,
+            Drill::Launcher => Some(self.launch(ball, state.arena)),
+        }
+    }
+
+    fn on_shot(&mut self) {
+        self.shots += 1;
+    }
+
+    fn on_hit(
+        &mut self,
+        state: &mut MatchState,
+        side: Side,
+        paddle: &mut Entity,
+        ball: &Entity,
+        smash: bool,
+    ) {
+        self.classic.on_hit(state, side, paddle, ball, smash);
+
+        let speed = vector_length(ball.velocity);
+        self.returns += 1;
+        self.best_returns = self.best_returns.max(self.returns);
+        self.top_speed = self.top_speed.max(speed);
+        if smash {
+            self.smashes += 1;
+        }
+    }
+
+    fn on_late_smash(&mut self, _side: Side) {
+        self.smashes += 1;
+    }
+
+    /// The rally drill has a wall where the AI would be, and the launcher catches balls sent
+    /// back to it before firing the next one
+    fn on_wall(&mut self, _state: &mut MatchState, side: Side) -> Wall {
+        if side == Side::Ai && self.drill == Drill::Rally {
+            return Wall::Bounce;
+        }
+        if side == Side::Player {
+            self.returns = 0;
+        }
+        self.reload();
+        Wall::Out
+    }
+
+    /// A miss puts the paddle back to its starting size
+    fn on_point(&mut self, state: &mut MatchState, conceded: Side, paddles: [&mut Entity; 2]) {
+        self.classic.on_point(state, conceded, paddles);
+    }
+
+    fn on_tick(
+        &mut self,
+        state: &mut MatchState,
+        playing: bool,
+        frames: u32,
+        paddles: [&mut Entity; 2],
+    ) {
+        self.classic.on_tick(state, playing, frames, paddles);
+    }
+
+    /// Practice goes on until it's closed
+    fn is_match_over(&self, _state: &MatchState) -> Option<Side> {
+        None
+    }
+
+    fn score_text(&self, _state: &MatchState) -> Option<String> {
+        Some(self.text())
+    }
+
+    fn has_opponent(&self) -> bool {
+        false
+    }
+
+    fn keeps_stats(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sfml::{graphics::Color, system::Vector2};
+
+    use super::{GameMode, MatchState, Practice};
+    use crate::{config::Config, entity::Entity, serve::Side};
+
+    fn paddle(name: &str) -> Entity {
+        Entity::new(
+            Vector2::new(100.0, 100.0),
+            20,
+            120,
+            name.to_string(),
+            Color::WHITE,
+            0.75,
+            0.75,
+        )
+    }
+
+    #[test]
+    fn a_miss_restores_the_paddle() {
+        let mut practice = Practice::new(&Config::default());
+        let mut state = MatchState {
+            score: (0, 0),
+            ball_speed: 10.0,
+            frames: 0,
+            balls: 1,
+            new_balls: Vec::new(),
+            arena: Vector2::new(1920, 1080),
+        };
+        let mut player = paddle("Player");
+        let mut wall = paddle("AI");
+
+        // Shrinking over time until it's as small as it goes
+        for _ in 0..20_000 {
+            practice.on_tick(&mut state, true, 1, [&mut player, &mut wall]);
+        }
+        assert_eq!(player.scale.y, Config::default().paddle_sizing.min_scale);
+
+        practice.on_wall(&mut state, Side::Player);
+        practice.on_point(&mut state, Side::Player, [&mut player, &mut wall]);
+        assert_eq!(player.scale.y, 1.0);
+    }
+}
@@ -0,0 +1,150 @@
+//! The most points before time runs out, going to sudden death when it's tied at the end
+
+use super::{
+    classic::{self, Classic},
+    GameMode, MatchState,
+};
+use crate::{config::Config, entity::Entity, serve::Side};
+
+// The game runs at 60 frames a second, so that's how the clock counts
+const FRAMES_PER_SECOND: u32 = 60;
+
+pub struct TimeAttack {
+    classic: Classic,
+    // Length of a match, in frames
+    time_limit: u32,
+}
+
+impl TimeAttack {
+    pub fn new(config: &Config) -> TimeAttack {
+        // Taking all of the other side's lives still ends it early
+        TimeAttack {
+            classic: Classic::first_to(config, config.lives),
+            time_limit: (config.time_limit * 60.0 * FRAMES_PER_SECOND as f32).round() as u32,
+        }
+    }
+
+    fn time_up(&self, state: &MatchState) -> bool {
+        state.frames >= self.time_limit
+    }
+}
+
+impl GameMode for TimeAttack {
+    fn name(&self) -> &'static str {
+        "time_attack"
+    }
+
+    fn on_hit(
+        &mut self,
+        state: &mut MatchState,
+        side: Side,
+        paddle: &mut Entity,
+        ball: &Entity,
+        smash: bool,
+    ) {
+        self.classic.on_hit(state, side, paddle, ball, smash);
+    }
+
+    fn on_point(&mut self, state: &mut MatchState, conceded: Side, paddles: [&mut Entity; 2]) {
+        self.classic.on_point(state, conceded, paddles);
+    }
+
+    fn on_tick(
+        &mut self,
+        state: &mut MatchState,
+        playing: bool,
+        frames: u32,
+        paddles: [&mut Entity; 2],
+    ) {
+        self.classic.on_tick(state, playing, frames, paddles);
+    }
+
+    /// Whoever is ahead when time's up, a point being played when it runs out still counts
+    fn is_match_over(&self, state: &MatchState) -> Option<Side> {
+        match self.classic.is_match_over(state) {
+            Some(winner) => Some(winner),
+            None if self.time_up(state) => classic::leader(state.score),
+            None => None,
+        }
+    }
+
+    fn clock(&self, state: &MatchState) -> Option<String> {
+        // Level at the end, so whoever scores next wins
+        if self.time_up(state) && classic::leader(state.score).is_none() {
+            return Some(String::from("Sudden death"));
+        }
+        // Rounded up, so the clock reads 0:00 only once time's up
+        let seconds = self
+            .time_limit
+            .saturating_sub(state.frames)
+            .div_ceil(FRAMES_PER_SECOND);
+        Some(format!("{}:{:02}", seconds / 60, seconds % 60))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sfml::system::Vector2;
+
+    use super::{GameMode, MatchState, TimeAttack, FRAMES_PER_SECOND};
+    use crate::{config::Config, serve::Side};
+
+    fn state(score: (u8, u8), frames: u32) -> MatchState {
+        MatchState {
+            score,
+            ball_speed: 10.0,
+            frames,
+            balls: 1,
+            new_balls: Vec::new(),
+            arena: Vector2::new(1920, 1080),
+        }
+    }
+
+    // A minute long match
+    fn time_attack(lives: u8) -> TimeAttack {
+        TimeAttack::new(&Config {
+            time_limit: 1.0,
+            lives,
+            ..Config::default()
+        })
+    }
+
+    const MINUTE: u32 = 60 * FRAMES_PER_SECOND;
+
+    #[test]
+    fn whoever_is_ahead_wins_when_time_is_up() {
+        let mode = time_attack(0);
+        assert!(mode.is_match_over(&state((30, 2), MINUTE - 1)).is_none());
+        assert!(mode.is_match_over(&state((3, 2), MINUTE)) == Some(Side::Player));
+        assert!(mode.is_match_over(&state((1, 2), MINUTE + 60)) == Some(Side::Ai));
+    }
+
+    #[test]
+    fn level_at_the_end_goes_to_sudden_death() {
+        let mode = time_attack(0);
+        let tied = state((2, 2), MINUTE);
+        assert!(mode.is_match_over(&tied).is_none());
+        assert_eq!(mode.clock(&tied).as_deref(), Some("Sudden death"));
+        assert!(mode.is_match_over(&state((2, 3), MINUTE + 1)) == Some(Side::Ai));
+    }
+
+    #[test]
+    fn clock_counts_down_in_whole_seconds() {
+        let mode = time_attack(0);
+        assert_eq!(mode.clock(&state((0, 0), 0)).as_deref(), Some("1:00"));
+        assert_eq!(mode.clock(&state((0, 0), 1)).as_deref(), Some("1:00"));
+        assert_eq!(
+            mode.clock(&state((1, 0), MINUTE - FRAMES_PER_SECOND))
+                .as_deref(),
+            Some("0:01")
+        );
+        assert_eq!(mode.clock(&state((1, 0), MINUTE)).as_deref(), Some("0:00"));
+    }
+
+    #[test]
+    fn taking_every_life_ends_it_early() {
+        let mode = time_attack(3);
+        assert!(mode.is_match_over(&state((3, 1), 0)) == Some(Side::Player));
+        assert!(mode.is_match_over(&state((2, 1), 0)).is_none());
+    }
+}
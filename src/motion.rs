@@ -0,0 +1,89 @@
+use std::str::FromStr;
+
+use sfml::system::Vector2;
+
+use crate::entity::Entity;
+
+/// How the score board moves around the play area
+#[derive(Clone, Copy, PartialEq)]
+pub enum MotionPattern {
+    Fixed,
+    Patrol,
+    SineWave,
+    DriftToTrailing,
+}
+
+impl FromStr for MotionPattern {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "fixed" => Ok(MotionPattern::Fixed),
+            "patrol" => Ok(MotionPattern::Patrol),
+            "sine" => Ok(MotionPattern::SineWave),
+            "drift" => Ok(MotionPattern::DriftToTrailing),
+            _ => Err(format!(
+                "unknown motion `{}` (expected fixed, patrol, sine or drift)",
+                value
+            )),
+        }
+    }
+}
+
+pub struct Mover {
+    pub pattern: MotionPattern,
+    speed: f32,
+    range: f32,
+    direction: f32,
+    frame: u32,
+}
+
+impl Mover {
+    pub fn new(pattern: MotionPattern, speed: f32, range: f32) -> Mover {
+        Mover {
+            pattern,
+            speed,
+            range,
+            direction: 1.0,
+            frame: 0,
+        }
+    }
+
+    /// Sets the entity's velocity for this frame and moves it
+    ///
+    /// The velocity is left on the entity so the ball picks it up when it bounces off
+    pub fn update(&mut self, entity: &mut Entity, score: (u8, u8)) {
+        let home_x = entity.init_position.x;
+        let offset_x = entity.position.x - home_x;
+
+        let velocity_x = match self.pattern {
+            MotionPattern::Fixed => 0.0,
+            MotionPattern::Patrol => {
+                if offset_x >= self.range {
+                    self.direction = -1.0;
+                } else if offset_x <= -self.range {
+                    self.direction = 1.0;
+                }
+                self.speed * self.direction
+            }
+            MotionPattern::SineWave => {
+                let phase = self.frame as f32 * self.speed / self.range;
+                home_x + phase.sin() * self.range - entity.position.x
+            }
+            MotionPattern::DriftToTrailing => {
+                // Drift over the half of whoever is behind, to give them something to aim at
+                let target_x = match score.0.cmp(&score.1) {
+                    std::cmp::Ordering::Less => home_x - self.range,
+                    std::cmp::Ordering::Greater => home_x + self.range,
+                    std::cmp::Ordering::Equal => home_x,
+                };
+                ((target_x - entity.position.x) * 0.05).clamp(-self.speed, self.speed)
+            }
+        };
+
+        self.frame = self.frame.wrapping_add(1);
+
+        entity.velocity = Vector2::new(velocity_x, 0.0);
+        entity.r#move();
+    }
+}
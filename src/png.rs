@@ -0,0 +1,609 @@
+//! Just enough PNG to save and load 8-bit RGBA images without pulling in an image crate
+
+use sfml::system::Vector2u;
+
+const SIGNATURE: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+
+/// Encodes rows of RGBA pixels as a PNG
+pub fn encode(size: Vector2u, pixels: &[u8]) -> Vec<u8> {
+    let row_length = (size.x * 4) as usize;
+
+    // Every row gets the "none" filter, the compressor picks up the repeats on its own
+    let mut filtered = Vec::with_capacity((row_length + 1) * size.y as usize);
+    for row in pixels.chunks_exact(row_length) {
+        filtered.push(0);
+        filtered.extend_from_slice(row);
+    }
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&size.x.to_be_bytes());
+    header.extend_from_slice(&size.y.to_be_bytes());
+    // 8 bits per channel, RGBA, deflate, adaptive filtering, not interlaced
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let mut png = SIGNATURE.to_vec();
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &zlib_compress(&filtered));
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+// Only the snapshot tests read images back
+#[cfg(test)]
+/// Decodes a non-interlaced 8-bit RGB or RGBA PNG into rows of RGBA pixels
+pub fn decode(data: &[u8]) -> Result<(Vector2u, Vec<u8>), String> {
+    if data.len() < 8 || data[..8] != SIGNATURE {
+        return Err(String::from("not a PNG file"));
+    }
+
+    let mut size = Vector2u::new(0, 0);
+    let mut channels = 0;
+    let mut compressed = Vec::new();
+    let mut offset = 8;
+
+    while offset + 8 <= data.len() {
+        let length = u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap()) as usize;
+        let kind = &data[offset + 4..offset + 8];
+        let body = data
+            .get(offset + 8..offset + 8 + length)
+            .ok_or("truncated chunk")?;
+
+        match kind {
+            b"IHDR" => {
+                if body.len() < 13 {
+                    return Err(String::from("truncated header"));
+                }
+                size = Vector2u::new(
+                    u32::from_be_bytes(body[0..4].try_into().unwrap()),
+                    u32::from_be_bytes(body[4..8].try_into().unwrap()),
+                );
+                channels = match (body[8], body[9], body[12]) {
+                    (8, 6, 0) => 4,
+                    (8, 2, 0) => 3,
+                    _ => {
+                        return Err(String::from(
+                            "only 8-bit non-interlaced RGB(A) is supported",
+                        ))
+                    }
+                };
+            }
+            b"IDAT" => compressed.extend_from_slice(body),
+            b"IEND" => break,
+            _ => {}
+        }
+
+        // Skip the body and its CRC
+        offset += 12 + length;
+    }
+
+    if channels == 0 {
+        return Err(String::from("missing header"));
+    }
+    if compressed.len() < 2 {
+        return Err(String::from("missing image data"));
+    }
+
+    let filtered = inflate(&compressed[2..])?;
+    let stride = size.x as usize * channels;
+    if filtered.len() < (stride + 1) * size.y as usize {
+        return Err(String::from("truncated image data"));
+    }
+
+    let mut raw = vec![0u8; stride * size.y as usize];
+    for y in 0..size.y as usize {
+        let filter = filtered[y * (stride + 1)];
+        let line = &filtered[y * (stride + 1) + 1..(y + 1) * (stride + 1)];
+
+        for x in 0..stride {
+            let left = if x >= channels {
+                raw[y * stride + x - channels]
+            } else {
+                0
+            };
+            let up = if y > 0 { raw[(y - 1) * stride + x] } else { 0 };
+            let up_left = if y > 0 && x >= channels {
+                raw[(y - 1) * stride + x - channels]
+            } else {
+                0
+            };
+
+            let predicted = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((left as u16 + up as u16) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                _ => return Err(format!("unknown filter type {}", filter)),
+            };
+            raw[y * stride + x] = line[x].wrapping_add(predicted);
+        }
+    }
+
+    let pixels = if channels == 4 {
+        raw
+    } else {
+        raw.chunks_exact(3)
+            .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+            .collect()
+    };
+
+    Ok((size, pixels))
+}
+
+#[cfg(test)]
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = left as i16 + up as i16 - up_left as i16;
+    let distance_left = (estimate - left as i16).abs();
+    let distance_up = (estimate - up as i16).abs();
+    let distance_up_left = (estimate - up_left as i16).abs();
+
+    if distance_left <= distance_up && distance_left <= distance_up_left {
+        left
+    } else if distance_up <= distance_up_left {
+        up
+    } else {
+        up_left
+    }
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], body: &[u8]) {
+    png.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    let crc_start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(body);
+    let crc = crc32(&png[crc_start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+// Deflate tables, indexed by length and distance code
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+const WINDOW_SIZE: usize = 32768;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const MAX_CHAIN: usize = 16;
+
+struct BitWriter {
+    bytes: Vec<u8>,
+    bit_buffer: u32,
+    bit_count: u32,
+}
+
+impl BitWriter {
+    fn write_bits(&mut self, value: u32, count: u32) {
+        self.bit_buffer |= value << self.bit_count;
+        self.bit_count += count;
+        while self.bit_count >= 8 {
+            self.bytes.push(self.bit_buffer as u8);
+            self.bit_buffer >>= 8;
+            self.bit_count -= 8;
+        }
+    }
+
+    /// Huffman codes go out most significant bit first
+    fn write_code(&mut self, code: u32, length: u32) {
+        let reversed = code.reverse_bits() >> (32 - length);
+        self.write_bits(reversed, length);
+    }
+
+    fn write_literal(&mut self, symbol: u16) {
+        match symbol {
+            0..=143 => self.write_code(0x30 + symbol as u32, 8),
+            144..=255 => self.write_code(0x190 + (symbol as u32 - 144), 9),
+            256..=279 => self.write_code(symbol as u32 - 256, 7),
+            _ => self.write_code(0xC0 + (symbol as u32 - 280), 8),
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bit_count > 0 {
+            self.bytes.push(self.bit_buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+/// Compresses with LZ77 and the fixed Huffman codes, which suits flat-colored frames
+fn zlib_compress(data: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter {
+        bytes: vec![0x78, 0x01],
+        bit_buffer: 0,
+        bit_count: 0,
+    };
+
+    // One final block using the fixed codes
+    writer.write_bits(1, 1);
+    writer.write_bits(1, 2);
+
+    let hash = |position: usize| {
+        ((data[position] as usize) << 10
+            ^ (data[position + 1] as usize) << 5
+            ^ data[position + 2] as usize)
+            & 0x7FFF
+    };
+    let mut head = vec![usize::MAX; 0x8000];
+    let mut previous = vec![usize::MAX; data.len()];
+
+    let mut position = 0;
+    while position < data.len() {
+        let mut best_length = 0;
+        let mut best_distance = 0;
+
+        if position + MIN_MATCH <= data.len() {
+            let key = hash(position);
+            let mut candidate = head[key];
+            let mut chain = 0;
+
+            while candidate != usize::MAX
+                && position - candidate <= WINDOW_SIZE
+                && chain < MAX_CHAIN
+            {
+                let max_length = MAX_MATCH.min(data.len() - position);
+                let mut length = 0;
+                while length < max_length && data[candidate + length] == data[position + length] {
+                    length += 1;
+                }
+                if length > best_length {
+                    best_length = length;
+                    best_distance = position - candidate;
+                    if length == max_length {
+                        break;
+                    }
+                }
+                candidate = previous[candidate];
+                chain += 1;
+            }
+        }
+
+        let step = if best_length >= MIN_MATCH {
+            let length_code = LENGTH_BASE
+                .iter()
+                .rposition(|base| *base as usize <= best_length)
+                .unwrap();
+            writer.write_literal(257 + length_code as u16);
+            writer.write_bits(
+                (best_length - LENGTH_BASE[length_code] as usize) as u32,
+                LENGTH_EXTRA[length_code] as u32,
+            );
+
+            let distance_code = DISTANCE_BASE
+                .iter()
+                .rposition(|base| *base as usize <= best_distance)
+                .unwrap();
+            writer.write_code(distance_code as u32, 5);
+            writer.write_bits(
+                (best_distance - DISTANCE_BASE[distance_code] as usize) as u32,
+                DISTANCE_EXTRA[distance_code] as u32,
+            );
+            best_length
+        } else {
+            writer.write_literal(data[position] as u16);
+            1
+        };
+
+        // Remember where every sequence that was just passed over starts
+        let hashable_end = (position + step).min(data.len().saturating_sub(MIN_MATCH - 1));
+        for (inserted, previous_start) in previous
+            .iter_mut()
+            .enumerate()
+            .take(hashable_end)
+            .skip(position)
+        {
+            let key = hash(inserted);
+            *previous_start = head[key];
+            head[key] = inserted;
+        }
+        position += step;
+    }
+
+    writer.write_literal(256);
+
+    let mut bytes = writer.finish();
+    bytes.extend_from_slice(&adler32(data).to_be_bytes());
+    bytes
+}
+
+#[cfg(test)]
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    bit_buffer: u32,
+    bit_count: u32,
+}
+
+#[cfg(test)]
+impl BitReader<'_> {
+    fn bits(&mut self, count: u32) -> Result<u32, String> {
+        while self.bit_count < count {
+            let byte = *self
+                .data
+                .get(self.position)
+                .ok_or("unexpected end of data")?;
+            self.position += 1;
+            self.bit_buffer |= (byte as u32) << self.bit_count;
+            self.bit_count += 8;
+        }
+
+        let value = self.bit_buffer & ((1u64 << count) - 1) as u32;
+        self.bit_buffer >>= count;
+        self.bit_count -= count;
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+/// A canonical Huffman code, as the number of codes of each length and the symbols in code order
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+#[cfg(test)]
+impl Huffman {
+    fn new(lengths: &[u8]) -> Huffman {
+        let mut counts = [0u16; 16];
+        for length in lengths {
+            counts[*length as usize] += 1;
+        }
+        counts[0] = 0;
+
+        let mut offsets = [0u16; 16];
+        for length in 1..15 {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+
+        let mut symbols = vec![0u16; lengths.len()];
+        for (symbol, length) in lengths.iter().enumerate() {
+            if *length != 0 {
+                symbols[offsets[*length as usize] as usize] = symbol as u16;
+                offsets[*length as usize] += 1;
+            }
+        }
+
+        Huffman { counts, symbols }
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, String> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for length in 1..16 {
+            code |= reader.bits(1)? as i32;
+            let count = self.counts[length] as i32;
+            if code - count < first {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first += count;
+            first <<= 1;
+            code <<= 1;
+        }
+        Err(String::from("invalid Huffman code"))
+    }
+}
+
+#[cfg(test)]
+fn inflate(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut reader = BitReader {
+        data,
+        position: 0,
+        bit_buffer: 0,
+        bit_count: 0,
+    };
+    let mut output = Vec::new();
+
+    loop {
+        let last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => {
+                // Stored, starting on the next byte boundary
+                reader.bit_buffer = 0;
+                reader.bit_count = 0;
+                let header = data
+                    .get(reader.position..reader.position + 4)
+                    .ok_or("truncated stored block")?;
+                let length = u16::from_le_bytes([header[0], header[1]]) as usize;
+                reader.position += 4;
+                let stored = data
+                    .get(reader.position..reader.position + length)
+                    .ok_or("truncated stored block")?;
+                output.extend_from_slice(stored);
+                reader.position += length;
+            }
+            1 => {
+                let mut lengths = [0u8; 288];
+                lengths[..144].fill(8);
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                lengths[280..].fill(8);
+                let literals = Huffman::new(&lengths);
+                let distances = Huffman::new(&[5; 30]);
+                inflate_block(&mut reader, &mut output, &literals, &distances)?;
+            }
+            2 => {
+                let literal_count = reader.bits(5)? as usize + 257;
+                let distance_count = reader.bits(5)? as usize + 1;
+                let code_length_count = reader.bits(4)? as usize + 4;
+
+                const ORDER: [usize; 19] = [
+                    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+                ];
+                let mut code_lengths = [0u8; 19];
+                for index in ORDER.iter().take(code_length_count) {
+                    code_lengths[*index] = reader.bits(3)? as u8;
+                }
+                let code_length_code = Huffman::new(&code_lengths);
+
+                let mut lengths = vec![0u8; literal_count + distance_count];
+                let mut index = 0;
+                while index < lengths.len() {
+                    let symbol = code_length_code.decode(&mut reader)?;
+                    let (value, repeat) = match symbol {
+                        0..=15 => (symbol as u8, 1),
+                        16 => {
+                            let previous = *lengths
+                                .get(index.wrapping_sub(1))
+                                .ok_or("repeat with no previous length")?;
+                            (previous, 3 + reader.bits(2)? as usize)
+                        }
+                        17 => (0, 3 + reader.bits(3)? as usize),
+                        _ => (0, 11 + reader.bits(7)? as usize),
+                    };
+                    if index + repeat > lengths.len() {
+                        return Err(String::from("too many code lengths"));
+                    }
+                    lengths[index..index + repeat].fill(value);
+                    index += repeat;
+                }
+
+                let literals = Huffman::new(&lengths[..literal_count]);
+                let distances = Huffman::new(&lengths[literal_count..]);
+                inflate_block(&mut reader, &mut output, &literals, &distances)?;
+            }
+            _ => return Err(String::from("invalid block type")),
+        }
+
+        if last {
+            return Ok(output);
+        }
+    }
+}
+
+#[cfg(test)]
+fn inflate_block(
+    reader: &mut BitReader,
+    output: &mut Vec<u8>,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> Result<(), String> {
+    loop {
+        let symbol = literals.decode(reader)? as usize;
+        match symbol {
+            0..=255 => output.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let length_code = symbol - 257;
+                if length_code >= LENGTH_BASE.len() {
+                    return Err(String::from("invalid length code"));
+                }
+                let length = LENGTH_BASE[length_code] as usize
+                    + reader.bits(LENGTH_EXTRA[length_code] as u32)? as usize;
+
+                let distance_code = distances.decode(reader)? as usize;
+                if distance_code >= DISTANCE_BASE.len() {
+                    return Err(String::from("invalid distance code"));
+                }
+                let distance = DISTANCE_BASE[distance_code] as usize
+                    + reader.bits(DISTANCE_EXTRA[distance_code] as u32)? as usize;
+
+                if distance > output.len() {
+                    return Err(String::from("distance too far back"));
+                }
+                let start = output.len() - distance;
+                for offset in 0..length {
+                    output.push(output[start + offset]);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sfml::system::Vector2u;
+
+    use super::{adler32, crc32, decode, encode, inflate, zlib_compress};
+
+    // Bytes that are hard to compress, from a fixed linear congruential generator
+    fn noise(length: usize) -> Vec<u8> {
+        let mut state: u32 = 12345;
+        (0..length)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                (state >> 16) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn checksums_match_known_values() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+
+    #[test]
+    fn compressed_data_inflates_back() {
+        let mut data = noise(1000);
+        // Long runs, repeats further back than the longest match and a tail too short to match
+        data.extend(std::iter::repeat_n(7, 600));
+        data.extend_from_within(..40_000.min(data.len()));
+        data.extend(noise(70_000));
+        data.extend_from_slice(b"ab");
+
+        for length in [0, 1, 2, 3, 258, data.len()] {
+            let compressed = zlib_compress(&data[..length]);
+            // Skip the two byte zlib header and the Adler-32 trailer
+            let inflated = inflate(&compressed[2..compressed.len() - 4]).unwrap();
+            assert_eq!(inflated, data[..length]);
+            assert_eq!(
+                compressed[compressed.len() - 4..],
+                adler32(&data[..length]).to_be_bytes()
+            );
+        }
+    }
+
+    #[test]
+    fn encoded_image_decodes_back() {
+        let size = Vector2u::new(37, 21);
+        let mut pixels = noise((size.x * size.y * 4) as usize);
+        // A flat-colored band, like most of a frame
+        pixels[400..2000].fill(200);
+
+        let (decoded_size, decoded) = decode(&encode(size, &pixels)).unwrap();
+        assert_eq!(decoded_size, size);
+        assert_eq!(decoded, pixels);
+    }
+
+    #[test]
+    fn decoding_rejects_other_files() {
+        assert!(decode(b"not a png").is_err());
+        let png = encode(Vector2u::new(2, 2), &[0; 16]);
+        assert!(decode(&png[..png.len() / 2]).is_err());
+    }
+}
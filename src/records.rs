@@ -0,0 +1,465 @@
+use std::{fs, io, path::PathBuf};
+
+use crate::{serve::Side, stats::data_dir};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum RecordKind {
+    LongestRally,
+    TopSpeed,
+    SmashStreak,
+    FastestWin,
+}
+
+impl RecordKind {
+    const ALL: [RecordKind; 4] = [
+        RecordKind::LongestRally,
+        RecordKind::TopSpeed,
+        RecordKind::SmashStreak,
+        RecordKind::FastestWin,
+    ];
+
+    fn key(self) -> &'static str {
+        match self {
+            RecordKind::LongestRally => "longest_rally",
+            RecordKind::TopSpeed => "top_speed",
+            RecordKind::SmashStreak => "smash_streak",
+            RecordKind::FastestWin => "fastest_win",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RecordKind::LongestRally => "Longest rally",
+            RecordKind::TopSpeed => "Top speed",
+            RecordKind::SmashStreak => "Smash streak",
+            RecordKind::FastestWin => "Fastest win",
+        }
+    }
+
+    /// Fastest wins count down, everything else counts up
+    fn beats(self, value: f32, record: f32) -> bool {
+        match self {
+            RecordKind::FastestWin => value < record,
+            _ => value > record,
+        }
+    }
+
+    fn format(self, value: f32) -> String {
+        match self {
+            RecordKind::LongestRally => format!("{} returns", value),
+            RecordKind::TopSpeed => format!("{:.1} pixels per frame", value),
+            RecordKind::SmashStreak => format!("{} smashes", value),
+            RecordKind::FastestWin => format!("{:.1} seconds", value),
+        }
+    }
+}
+
+struct Record {
+    mode: String,
+    kind: RecordKind,
+    value: f32,
+    holder: String,
+}
+
+/// The best results on this machine, kept per game mode in `records.toml` in the data directory
+pub struct Records {
+    records: Vec<Record>,
+    // Where they're saved, None without a data directory
+    path: Option<PathBuf>,
+    // Lines were skipped, so the original is backed up before it's overwritten
+    damaged: bool,
+}
+
+fn records_path() -> Option<PathBuf> {
+    Some(data_dir()?.join("records.toml"))
+}
+
+impl Records {
+    /// Reads every record it can, skipping damaged lines instead of giving up on the file
+    pub fn load() -> Records {
+        Records::load_from(records_path())
+    }
+
+    fn load_from(path: Option<PathBuf>) -> Records {
+        let mut records = Records {
+            records: Vec::new(),
+            path,
+            damaged: false,
+        };
+        let Some(Ok(data)) = records.path.as_ref().map(fs::read) else {
+            return records;
+        };
+        // Garbled bytes only spoil the lines they're on
+        let source = String::from_utf8_lossy(&data);
+
+        let mut mode = String::new();
+        for (index, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line.starts_with('[') && line.ends_with(']') {
+                mode = line[1..line.len() - 1].trim().to_string();
+                continue;
+            }
+
+            match records.parse_record(&mode, line) {
+                Some(record) => records.records.push(record),
+                None => {
+                    println!("Skipping damaged record on line {}: {}", index + 1, line);
+                    records.damaged = true;
+                }
+            }
+        }
+
+        records
+    }
+
+    /// `key = value holder`
+    fn parse_record(&self, mode: &str, line: &str) -> Option<Record> {
+        let (key, rest) = line.split_once('=')?;
+        let (value, holder) = rest.trim().split_once(' ').unwrap_or((rest.trim(), ""));
+        let kind = *RecordKind::ALL
+            .iter()
+            .find(|kind| kind.key() == key.trim())?;
+        let value: f32 = value.parse().ok().filter(|value: &f32| value.is_finite())?;
+
+        if mode.is_empty() || self.get(mode, kind).is_some() {
+            return None;
+        }
+
+        Some(Record {
+            mode: mode.to_string(),
+            kind,
+            value,
+            holder: holder.trim().to_string(),
+        })
+    }
+
+    fn get(&self, mode: &str, kind: RecordKind) -> Option<&Record> {
+        self.records
+            .iter()
+            .find(|record| record.mode == mode && record.kind == kind)
+    }
+
+    /// Saves `value` if it beats the current record, returning whether it did
+    pub fn submit(&mut self, mode: &str, kind: RecordKind, value: f32, holder: &str) -> bool {
+        match self
+            .records
+            .iter_mut()
+            .find(|record| record.mode == mode && record.kind == kind)
+        {
+            Some(record) if !kind.beats(value, record.value) => return false,
+            Some(record) => {
+                record.value = value;
+                record.holder = holder.to_string();
+            }
+            None => self.records.push(Record {
+                mode: mode.to_string(),
+                kind,
+                value,
+                holder: holder.to_string(),
+            }),
+        }
+
+        if let Err(err) = self.save() {
+            println!("Couldn't save the records: {}", err);
+        }
+        true
+    }
+
+    fn modes(&self) -> Vec<&str> {
+        let mut modes: Vec<&str> = Vec::new();
+        for record in &self.records {
+            if !modes.contains(&record.mode.as_str()) {
+                modes.push(&record.mode);
+            }
+        }
+        modes
+    }
+
+    fn save(&mut self) -> io::Result<()> {
+        let path = self
+            .path
+            .as_ref()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        // Keep the original around, only what could be read is written back
+        if self.damaged {
+            fs::copy(path, path.with_extension("toml.bak"))?;
+            self.damaged = false;
+        }
+
+        let mut contents = String::new();
+        for mode in self.modes() {
+            contents += &format!("[{}]\n", mode);
+            for kind in RecordKind::ALL {
+                if let Some(record) = self.get(mode, kind) {
+                    contents += &format!("{} = {} {}\n", kind.key(), record.value, record.holder);
+                }
+            }
+            contents += "\n";
+        }
+
+        fs::write(path, contents)
+    }
+
+    pub fn print(&self) {
+        if self.records.is_empty() {
+            println!("No records yet, play a match first");
+        }
+
+        for mode in self.modes() {
+            println!("{}", mode);
+            for kind in RecordKind::ALL {
+                if let Some(record) = self.get(mode, kind) {
+                    println!(
+                        "  {}: {} by {}",
+                        kind.label(),
+                        kind.format(record.value),
+                        record.holder
+                    );
+                }
+            }
+        }
+    }
+}
+
+// How long a broken record is announced on the score board
+const NOTICE_FRAMES: u32 = 180;
+
+/// Watches a match for broken records, announcing each new one for a few seconds
+pub struct RecordWatch {
+    // None when records aren't being kept
+    records: Option<Records>,
+    mode: String,
+    holder: String,
+    smash_streak: u32,
+    // Streak before the last plain return, in case it gets smashed late
+    previous_streak: u32,
+    notice: Option<(RecordKind, u32)>,
+}
+
+impl RecordWatch {
+    pub fn new(mode: &str, holder: &str) -> RecordWatch {
+        RecordWatch {
+            records: Some(Records::load()),
+            mode: mode.to_string(),
+            holder: holder.to_string(),
+            smash_streak: 0,
+            previous_streak: 0,
+            notice: None,
+        }
+    }
+
+    /// Watches nothing, for matches that shouldn't set records
+    pub fn disabled() -> RecordWatch {
+        RecordWatch {
+            records: None,
+            mode: String::new(),
+            holder: String::new(),
+            smash_streak: 0,
+            previous_streak: 0,
+            notice: None,
+        }
+    }
+
+    fn submit(&mut self, kind: RecordKind, value: f32) {
+        let Some(records) = self.records.as_mut() else {
+            return;
+        };
+        if value <= 0.0 {
+            return;
+        }
+        if records.submit(&self.mode, kind, value, &self.holder) {
+            println!("New record! {}: {}", kind.label(), kind.format(value));
+            self.notice = Some((kind, NOTICE_FRAMES));
+        }
+    }
+
+    pub fn on_return(&mut self, side: Side, smashed: bool) {
+        if side != Side::Player {
+            return;
+        }
+
+        if smashed {
+            self.smash_streak += 1;
+            self.submit(RecordKind::SmashStreak, self.smash_streak as f32);
+        } else {
+            self.previous_streak = self.smash_streak;
+            self.smash_streak = 0;
+        }
+    }
+
+    /// A smash just after the ball left the paddle turns the last return into a smash
+    pub fn on_late_smash(&mut self, side: Side) {
+        if side == Side::Player {
+            self.smash_streak = self.previous_streak;
+            self.on_return(side, true);
+        }
+    }
+
+    pub fn on_point(&mut self, rally: u32, top_speed: f32) {
+        self.submit(RecordKind::LongestRally, rally as f32);
+        self.submit(RecordKind::TopSpeed, top_speed);
+    }
+
+    pub fn on_win(&mut self, seconds: f32) {
+        self.submit(RecordKind::FastestWin, seconds);
+    }
+
+    pub fn update(&mut self) {
+        if let Some((_, frames)) = &mut self.notice {
+            *frames -= 1;
+            if *frames == 0 {
+                self.notice = None;
+            }
+        }
+    }
+
+    /// The record being announced and how many frames are left of it
+    pub fn notice(&self) -> Option<(String, u32)> {
+        self.notice
+            .map(|(kind, frames)| (format!("New record! {}", kind.label()), frames))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::PathBuf, process};
+
+    use super::{RecordKind, Records};
+
+    /// A records file of its own for each test, holding `data`
+    fn records_file(test: &str, data: &[u8]) -> PathBuf {
+        let dir = env::temp_dir().join(format!("window-pong-{}-{}", process::id(), test));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("records.toml");
+        fs::write(&path, data).unwrap();
+        path
+    }
+
+    fn value(records: &Records, mode: &str, kind: RecordKind) -> Option<f32> {
+        records.get(mode, kind).map(|record| record.value)
+    }
+
+    #[test]
+    fn damaged_lines_are_skipped() {
+        let path = records_file(
+            "damaged_lines",
+            b"top_speed = 30 Sam\n\
+              [classic]\n\
+              longest_rally = 12 Sam\n\
+              top_speed = fast Sam\n\
+              no equals sign\n\
+              smash_streak = inf Sam\n\
+              best_hair = 10 Sam\n\
+              fastest_win = 61.5 Alex\n",
+        );
+        let records = Records::load_from(Some(path.clone()));
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        assert!(records.damaged);
+        assert_eq!(records.records.len(), 2);
+        assert_eq!(
+            value(&records, "classic", RecordKind::LongestRally),
+            Some(12.0)
+        );
+        assert_eq!(
+            value(&records, "classic", RecordKind::FastestWin),
+            Some(61.5)
+        );
+        assert_eq!(
+            records
+                .get("classic", RecordKind::FastestWin)
+                .unwrap()
+                .holder,
+            "Alex"
+        );
+    }
+
+    #[test]
+    fn bad_utf8_only_spoils_its_line() {
+        let path = records_file(
+            "bad_utf8",
+            b"[classic]\nlongest_rally = 12 Sam\ntop_speed = 3\xff0 Sam\nsmash_streak = 4 \xfe\n",
+        );
+        let records = Records::load_from(Some(path.clone()));
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        assert!(records.damaged);
+        assert_eq!(
+            value(&records, "classic", RecordKind::LongestRally),
+            Some(12.0)
+        );
+        assert_eq!(value(&records, "classic", RecordKind::TopSpeed), None);
+        // A garbled holder still holds the record
+        assert_eq!(
+            value(&records, "classic", RecordKind::SmashStreak),
+            Some(4.0)
+        );
+    }
+
+    #[test]
+    fn duplicate_sections_keep_the_first_record() {
+        let path = records_file(
+            "duplicate_sections",
+            b"[classic]\nlongest_rally = 12 Sam\n\n[multiball]\nlongest_rally = 3 Sam\n\n\
+              [classic]\nlongest_rally = 40 Alex\ntop_speed = 25 Alex\n",
+        );
+        let records = Records::load_from(Some(path.clone()));
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        assert!(records.damaged);
+        assert_eq!(
+            value(&records, "classic", RecordKind::LongestRally),
+            Some(12.0)
+        );
+        assert_eq!(value(&records, "classic", RecordKind::TopSpeed), Some(25.0));
+        assert_eq!(
+            value(&records, "multiball", RecordKind::LongestRally),
+            Some(3.0)
+        );
+    }
+
+    #[test]
+    fn damaged_file_is_backed_up_on_save() {
+        let original = b"[classic]\nlongest_rally = 12 Sam\ntop_speed = ??? Sam\n";
+        let path = records_file("backup", original);
+        let backup = path.with_extension("toml.bak");
+
+        // Reading alone leaves the directory as it was
+        let mut records = Records::load_from(Some(path.clone()));
+        assert!(!backup.exists());
+
+        assert!(records.submit("classic", RecordKind::LongestRally, 20.0, "Alex"));
+        assert_eq!(fs::read(&backup).unwrap(), original);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "[classic]\nlongest_rally = 20 Alex\n\n"
+        );
+
+        // Later saves keep the backup of the damaged file
+        assert!(records.submit("classic", RecordKind::TopSpeed, 30.0, "Alex"));
+        assert_eq!(fs::read(&backup).unwrap(), original);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn clean_file_isnt_backed_up() {
+        let path = records_file("clean", b"[classic]\nlongest_rally = 12 Sam\n");
+        let mut records = Records::load_from(Some(path.clone()));
+
+        assert!(!records.damaged);
+        assert!(!records.submit("classic", RecordKind::LongestRally, 10.0, "Alex"));
+        assert!(records.submit("classic", RecordKind::LongestRally, 14.0, "Alex"));
+        assert!(!path.with_extension("toml.bak").exists());
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
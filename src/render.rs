@@ -0,0 +1,117 @@
+pub mod offscreen;
+pub mod single_window;
+pub mod windows;
+
+use std::str::FromStr;
+
+use sfml::{
+    graphics::Color,
+    system::{Vector2f, Vector2i, Vector2u},
+    window::Event,
+};
+
+use crate::error::GameError;
+
+/// Something drawn inside an entity's window, in the window's view coordinates
+pub enum Primitive {
+    Rect {
+        position: Vector2f,
+        size: Vector2f,
+        color: Color,
+    },
+    Circle {
+        center: Vector2f,
+        radius: f32,
+        color: Color,
+    },
+    /// Lines of text lined up on `position` by `align`, vertically centered on it
+    Text {
+        string: String,
+        position: Vector2f,
+        size: u32,
+        align: Align,
+        color: Color,
+    },
+}
+
+/// Which part of each line of text sits on its position
+#[derive(Clone, Copy, PartialEq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+impl FromStr for Align {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "left" => Ok(Align::Left),
+            "center" => Ok(Align::Center),
+            "right" => Ok(Align::Right),
+            _ => Err(format!(
+                "unknown alignment `{}` (expected left, center or right)",
+                value
+            )),
+        }
+    }
+}
+
+/// One entity's window as it should appear on the desktop this frame
+pub struct SceneWindow {
+    pub name: String,
+    // Top left corner, in desktop coordinates
+    pub position: Vector2i,
+    pub size: Vector2u,
+    // The coordinate space the primitives are drawn in, stretched to fill `size`
+    pub view_size: Vector2u,
+    pub background: Color,
+    pub primitives: Vec<Primitive>,
+}
+
+/// Everything on screen for one frame, windows later in the list are on top
+pub struct Scene {
+    pub windows: Vec<SceneWindow>,
+}
+
+pub trait Renderer {
+    /// Opens the windows for a scene ahead of the first frame, so failures show up at startup
+    fn open(&mut self, _scene: &Scene) -> Result<(), GameError> {
+        Ok(())
+    }
+
+    fn render(&mut self, scene: &Scene);
+
+    /// Input events since the last call, from whichever window takes input
+    fn poll_events(&mut self) -> Vec<Event>;
+
+    /// Where the mouse is in the scene's desktop coordinates, when the renderer can tell
+    fn mouse_position(&self) -> Option<Vector2f> {
+        None
+    }
+
+    /// Where the named window was dragged to since the game last put it somewhere, if it was
+    fn dragged_position(&mut self, _name: &str) -> Option<Vector2i> {
+        None
+    }
+
+    /// Names of the windows closed since the last call, they open again on the next frame
+    fn closed_windows(&mut self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Hands focus to another window, for when the one taking input is going away
+    fn set_input_window(&mut self, _name: &str) {}
+}
+
+/// Draws nothing, for matches nobody is watching
+pub struct NullRenderer;
+
+impl Renderer for NullRenderer {
+    fn render(&mut self, _scene: &Scene) {}
+
+    fn poll_events(&mut self) -> Vec<Event> {
+        Vec::new()
+    }
+}
@@ -0,0 +1,320 @@
+use sfml::{
+    graphics::Color,
+    system::{Vector2f, Vector2u},
+    window::Event,
+};
+
+use super::{Align, Primitive, Renderer, Scene, SceneWindow};
+
+/// Composes every window onto an in-memory RGBA image of the desktop, without a display or GPU
+pub struct OffscreenRenderer {
+    desktop_size: Vector2u,
+    image_size: Vector2u,
+    background: Color,
+    pixels: Vec<u8>,
+}
+
+/// Maps a window's view coordinates onto the image
+struct WindowTransform {
+    origin: Vector2f,
+    scale: Vector2f,
+    // Window bounds in image pixels, everything drawn is clipped to them
+    clip: (i32, i32, i32, i32),
+}
+
+impl WindowTransform {
+    fn new(window: &SceneWindow, image_scale: Vector2f) -> WindowTransform {
+        let origin = Vector2f::new(
+            window.position.x as f32 * image_scale.x,
+            window.position.y as f32 * image_scale.y,
+        );
+        let size = Vector2f::new(
+            window.size.x as f32 * image_scale.x,
+            window.size.y as f32 * image_scale.y,
+        );
+
+        WindowTransform {
+            origin,
+            scale: Vector2f::new(
+                size.x / window.view_size.x.max(1) as f32,
+                size.y / window.view_size.y.max(1) as f32,
+            ),
+            clip: (
+                origin.x.round() as i32,
+                origin.y.round() as i32,
+                (origin.x + size.x).round() as i32,
+                (origin.y + size.y).round() as i32,
+            ),
+        }
+    }
+
+    fn point(&self, point: Vector2f) -> Vector2f {
+        Vector2f::new(
+            self.origin.x + point.x * self.scale.x,
+            self.origin.y + point.y * self.scale.y,
+        )
+    }
+}
+
+impl OffscreenRenderer {
+    pub fn new(
+        desktop_size: Vector2u,
+        image_size: Vector2u,
+        background: Color,
+    ) -> OffscreenRenderer {
+        let mut renderer = OffscreenRenderer {
+            desktop_size,
+            image_size,
+            background,
+            pixels: vec![0; (image_size.x * image_size.y * 4) as usize],
+        };
+        renderer.clear();
+        renderer
+    }
+
+    pub fn image_size(&self) -> Vector2u {
+        self.image_size
+    }
+
+    /// The last rendered frame, as rows of RGBA pixels
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    fn clear(&mut self) {
+        for pixel in self.pixels.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&[self.background.r, self.background.g, self.background.b, 255]);
+        }
+    }
+
+    fn blend_pixel(&mut self, x: i32, y: i32, color: Color) {
+        let index = ((y as u32 * self.image_size.x + x as u32) * 4) as usize;
+        let alpha = color.a as u32;
+        let pixel = &mut self.pixels[index..index + 4];
+
+        for (channel, source) in pixel.iter_mut().zip([color.r, color.g, color.b]) {
+            *channel = ((source as u32 * alpha + *channel as u32 * (255 - alpha)) / 255) as u8;
+        }
+        pixel[3] = 255;
+    }
+
+    /// Fills every pixel whose center passes `inside`, within the bounds and the clip rect
+    fn fill<F: Fn(f32, f32) -> bool>(
+        &mut self,
+        bounds: (f32, f32, f32, f32),
+        clip: (i32, i32, i32, i32),
+        color: Color,
+        inside: F,
+    ) {
+        let min_x = (bounds.0.floor() as i32).max(clip.0).max(0);
+        let min_y = (bounds.1.floor() as i32).max(clip.1).max(0);
+        let max_x = (bounds.2.ceil() as i32)
+            .min(clip.2)
+            .min(self.image_size.x as i32);
+        let max_y = (bounds.3.ceil() as i32)
+            .min(clip.3)
+            .min(self.image_size.y as i32);
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                if inside(x as f32 + 0.5, y as f32 + 0.5) {
+                    self.blend_pixel(x, y, color);
+                }
+            }
+        }
+    }
+
+    fn fill_rect(
+        &mut self,
+        transform: &WindowTransform,
+        position: Vector2f,
+        size: Vector2f,
+        color: Color,
+    ) {
+        let top_left = transform.point(position);
+        let bottom_right = transform.point(position + size);
+        self.fill(
+            (top_left.x, top_left.y, bottom_right.x, bottom_right.y),
+            transform.clip,
+            color,
+            |x, y| x >= top_left.x && x < bottom_right.x && y >= top_left.y && y < bottom_right.y,
+        );
+    }
+
+    fn fill_circle(
+        &mut self,
+        transform: &WindowTransform,
+        center: Vector2f,
+        radius: f32,
+        color: Color,
+    ) {
+        // Stretched windows turn circles into ellipses, the same as they would on screen
+        let center = transform.point(center);
+        let radius = Vector2f::new(radius * transform.scale.x, radius * transform.scale.y);
+        if radius.x <= 0.0 || radius.y <= 0.0 {
+            return;
+        }
+
+        self.fill(
+            (
+                center.x - radius.x,
+                center.y - radius.y,
+                center.x + radius.x,
+                center.y + radius.y,
+            ),
+            transform.clip,
+            color,
+            |x, y| ((x - center.x) / radius.x).powi(2) + ((y - center.y) / radius.y).powi(2) <= 1.0,
+        );
+    }
+
+    /// Draws text with the built in block font, since there's no font rasterizer offscreen
+    fn fill_text(
+        &mut self,
+        transform: &WindowTransform,
+        string: &str,
+        position: Vector2f,
+        size: u32,
+        align: Align,
+        color: Color,
+    ) {
+        let unit = size as f32 / 10.0;
+        let lines: Vec<&str> = string.lines().collect();
+        let line_height = unit * (GLYPH_ROWS as f32 + 2.0);
+        let block_height = line_height * lines.len() as f32 - unit * 2.0;
+
+        for (line_index, line) in lines.iter().enumerate() {
+            let advance = unit * (GLYPH_COLUMNS as f32 + 1.0);
+            let line_width = advance * line.chars().count() as f32 - unit;
+            let left = match align {
+                Align::Left => position.x,
+                Align::Center => position.x - line_width / 2.0,
+                Align::Right => position.x - line_width,
+            };
+            let top_left = Vector2f::new(
+                left,
+                position.y - block_height / 2.0 + line_height * line_index as f32,
+            );
+
+            for (char_index, character) in line.chars().enumerate() {
+                let rows = glyph(character);
+                for (row_index, row) in rows.iter().enumerate() {
+                    for column in 0..GLYPH_COLUMNS {
+                        if row & (1 << (GLYPH_COLUMNS - 1 - column)) == 0 {
+                            continue;
+                        }
+
+                        let cell = Vector2f::new(
+                            top_left.x + advance * char_index as f32 + unit * column as f32,
+                            top_left.y + unit * row_index as f32,
+                        );
+                        self.fill_rect(transform, cell, Vector2f::new(unit, unit), color);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Renderer for OffscreenRenderer {
+    fn render(&mut self, scene: &Scene) {
+        self.clear();
+
+        let image_scale = Vector2f::new(
+            self.image_size.x as f32 / self.desktop_size.x.max(1) as f32,
+            self.image_size.y as f32 / self.desktop_size.y.max(1) as f32,
+        );
+
+        for window in &scene.windows {
+            let transform = WindowTransform::new(window, image_scale);
+
+            self.fill_rect(
+                &transform,
+                Vector2f::new(0.0, 0.0),
+                Vector2f::new(window.view_size.x as f32, window.view_size.y as f32),
+                window.background,
+            );
+
+            for primitive in &window.primitives {
+                match primitive {
+                    Primitive::Rect {
+                        position,
+                        size,
+                        color,
+                    } => self.fill_rect(&transform, *position, *size, *color),
+                    Primitive::Circle {
+                        center,
+                        radius,
+                        color,
+                    } => self.fill_circle(&transform, *center, *radius, *color),
+                    Primitive::Text {
+                        string,
+                        position,
+                        size,
+                        align,
+                        color,
+                    } => self.fill_text(&transform, string, *position, *size, *align, *color),
+                }
+            }
+        }
+    }
+
+    fn poll_events(&mut self) -> Vec<Event> {
+        Vec::new()
+    }
+}
+
+const GLYPH_COLUMNS: u32 = 5;
+const GLYPH_ROWS: usize = 7;
+
+/// 5x7 block glyphs, one byte per row with the leftmost column in the highest bit
+fn glyph(character: char) -> [u8; GLYPH_ROWS] {
+    match character.to_ascii_uppercase() {
+        ' ' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        'A' => [0x0E, 0x11, 0x11, 0x11, 0x1F, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        ':' => [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        '!' => [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04],
+        '/' => [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00],
+        '\'' => [0x0C, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00],
+        '(' => [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02],
+        ')' => [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08],
+        _ => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+    }
+}
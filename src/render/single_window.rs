@@ -0,0 +1,154 @@
+use sfml::{
+    graphics::{
+        Color, FloatRect, Font, RectangleShape, RenderStates, RenderTarget, RenderWindow, Shape,
+        Transform, View,
+    },
+    system::{Vector2, Vector2f, Vector2u},
+    window::{ContextSettings, Event, Style, VideoMode},
+    SfBox,
+};
+
+use crate::error::GameError;
+
+use super::{windows::draw_primitive, Primitive, Renderer, Scene};
+
+/// Draws the whole desktop scene inside one window, for window managers that won't let
+/// the game move its windows around
+pub struct SingleWindowRenderer {
+    window: RenderWindow,
+    view: SfBox<View>,
+    desktop_size: Vector2u,
+    font: SfBox<Font>,
+    backdrop: Color,
+}
+
+impl SingleWindowRenderer {
+    pub fn new(
+        font_data: &'static [u8],
+        desktop_size: Vector2u,
+        fullscreen: bool,
+        backdrop: Color,
+    ) -> Result<SingleWindowRenderer, GameError> {
+        let font = unsafe { Font::from_memory(font_data) }
+            .ok_or_else(|| GameError::Font(String::from("the score board font")))?;
+
+        let (video_mode, style) = if fullscreen {
+            (VideoMode::desktop_mode(), Style::FULLSCREEN)
+        } else {
+            (
+                VideoMode::new(desktop_size.x * 2 / 3, desktop_size.y * 2 / 3, 32),
+                Style::DEFAULT,
+            )
+        };
+        let window = RenderWindow::new(
+            video_mode,
+            "Window Pong",
+            style,
+            &ContextSettings::default(),
+        );
+        // SFML hands back a closed window instead of failing
+        if !window.is_open() {
+            return Err(GameError::Window(String::from("Window Pong")));
+        }
+
+        let view = View::from_rect(FloatRect::new(
+            0.0,
+            0.0,
+            desktop_size.x as f32,
+            desktop_size.y as f32,
+        ));
+
+        let mut renderer = SingleWindowRenderer {
+            window,
+            view,
+            desktop_size,
+            font,
+            backdrop,
+        };
+        let window_size = renderer.window.size();
+        renderer.letterbox(window_size.x, window_size.y);
+        Ok(renderer)
+    }
+
+    /// Fits the desktop into the window without stretching it, padding the rest with bars
+    fn letterbox(&mut self, width: u32, height: u32) {
+        let window_ratio = width as f32 / height.max(1) as f32;
+        let desktop_ratio = self.desktop_size.x as f32 / self.desktop_size.y.max(1) as f32;
+
+        let viewport = if window_ratio > desktop_ratio {
+            let view_width = desktop_ratio / window_ratio;
+            FloatRect::new((1.0 - view_width) / 2.0, 0.0, view_width, 1.0)
+        } else {
+            let view_height = window_ratio / desktop_ratio;
+            FloatRect::new(0.0, (1.0 - view_height) / 2.0, 1.0, view_height)
+        };
+
+        self.view.set_viewport(viewport);
+        self.window.set_view(&self.view);
+    }
+}
+
+impl Renderer for SingleWindowRenderer {
+    fn render(&mut self, scene: &Scene) {
+        self.window.clear(Color::BLACK);
+
+        // Desktop background, so the edges of the play area show inside the letterbox
+        let mut desktop = RectangleShape::with_size(Vector2::new(
+            self.desktop_size.x as f32,
+            self.desktop_size.y as f32,
+        ));
+        desktop.set_fill_color(self.backdrop);
+        self.window.draw(&desktop);
+
+        for scene_window in &scene.windows {
+            // Stretch the window's view onto its spot on the desktop
+            let mut transform = Transform::IDENTITY;
+            transform.translate(
+                scene_window.position.x as f32,
+                scene_window.position.y as f32,
+            );
+            transform.scale(
+                scene_window.size.x as f32 / scene_window.view_size.x.max(1) as f32,
+                scene_window.size.y as f32 / scene_window.view_size.y.max(1) as f32,
+            );
+            let mut states = RenderStates::DEFAULT;
+            states.transform = transform;
+
+            let background = Primitive::Rect {
+                position: Vector2::new(0.0, 0.0),
+                size: Vector2::new(
+                    scene_window.view_size.x as f32,
+                    scene_window.view_size.y as f32,
+                ),
+                color: scene_window.background,
+            };
+            draw_primitive(&mut self.window, &background, &self.font, &states);
+
+            for primitive in &scene_window.primitives {
+                draw_primitive(&mut self.window, primitive, &self.font, &states);
+            }
+        }
+
+        self.window.display();
+    }
+
+    fn mouse_position(&self) -> Option<Vector2f> {
+        Some(
+            self.window
+                .map_pixel_to_coords(self.window.mouse_position(), &self.view),
+        )
+    }
+
+    fn poll_events(&mut self) -> Vec<Event> {
+        let mut events = Vec::new();
+
+        while let Some(evt) = self.window.poll_event() {
+            if let Event::Resized { width, height } = evt {
+                self.letterbox(width, height);
+            }
+            events.push(evt);
+        }
+
+        events
+    }
+}
@@ -0,0 +1,287 @@
+use std::time::{Duration, Instant};
+
+use sfml::{
+    graphics::{
+        CircleShape, FloatRect, Font, RectangleShape, RenderStates, RenderTarget, RenderWindow,
+        Shape, Text, Transformable, View,
+    },
+    system::{Vector2, Vector2f, Vector2i, Vector2u},
+    window::{mouse, ContextSettings, Event, Style},
+    SfBox,
+};
+
+use crate::error::GameError;
+
+use super::{Align, Primitive, Renderer, Scene, SceneWindow};
+
+struct EntityWindow {
+    name: String,
+    window: RenderWindow,
+    position: Vector2i,
+    size: Vector2u,
+    // Size of the area drawn into, stretched over the window
+    view_size: Vector2u,
+    // When the game last moved the window
+    moved_at: Instant,
+}
+
+// How long the window manager gets to catch up with a move before the window's position is
+// trusted again, otherwise it looks like the player dragged it back
+const SETTLE_TIME: Duration = Duration::from_millis(100);
+
+/// Draws every entity into its own desktop window
+pub struct WindowRenderer {
+    windows: Vec<EntityWindow>,
+    font: SfBox<Font>,
+    // Name of the window that gets focus and whose focus changes are reported
+    input_window: String,
+    // Windows closed since the last `closed_windows`
+    closed: Vec<String>,
+}
+
+impl WindowRenderer {
+    pub fn new(font_data: &'static [u8], input_window: &str) -> Result<WindowRenderer, GameError> {
+        let font = unsafe { Font::from_memory(font_data) }
+            .ok_or_else(|| GameError::Font(String::from("the score board font")))?;
+
+        Ok(WindowRenderer {
+            windows: Vec::new(),
+            font,
+            input_window: input_window.to_string(),
+            closed: Vec::new(),
+        })
+    }
+
+    /// Gives focus back to the input window, new windows take it when they open
+    fn focus_input_window(&mut self) {
+        if let Some(entity_window) = self
+            .windows
+            .iter()
+            .find(|window| window.name == self.input_window)
+        {
+            entity_window.window.request_focus();
+        }
+    }
+
+    /// Finds the window for a scene window, opening one the first time it's seen
+    fn window_index(&mut self, scene_window: &SceneWindow) -> usize {
+        match self
+            .windows
+            .iter()
+            .position(|window| window.name == scene_window.name)
+        {
+            Some(index) => index,
+            None => {
+                let mut window = RenderWindow::new(
+                    (scene_window.view_size.x, scene_window.view_size.y),
+                    "",
+                    Style::CLOSE,
+                    &ContextSettings::default(),
+                );
+                window.set_position(scene_window.position);
+
+                self.windows.push(EntityWindow {
+                    name: scene_window.name.clone(),
+                    window,
+                    position: scene_window.position,
+                    size: scene_window.view_size,
+                    view_size: scene_window.view_size,
+                    moved_at: Instant::now(),
+                });
+                self.windows.len() - 1
+            }
+        }
+    }
+}
+
+impl Renderer for WindowRenderer {
+    fn open(&mut self, scene: &Scene) -> Result<(), GameError> {
+        for scene_window in &scene.windows {
+            let index = self.window_index(scene_window);
+            // SFML hands back a closed window instead of failing
+            if !self.windows[index].window.is_open() {
+                return Err(GameError::Window(scene_window.name.clone()));
+            }
+        }
+        self.focus_input_window();
+        Ok(())
+    }
+
+    fn render(&mut self, scene: &Scene) {
+        // Close the windows of anything that's left the scene
+        self.windows.retain(|window| {
+            scene
+                .windows
+                .iter()
+                .any(|scene_window| scene_window.name == window.name)
+        });
+
+        let open_windows = self.windows.len();
+        for scene_window in &scene.windows {
+            let index = self.window_index(scene_window);
+            let entity_window = &mut self.windows[index];
+
+            // Only move and resize when something changed, the window manager is slow at it
+            if entity_window.size != scene_window.size {
+                entity_window.window.set_size(scene_window.size);
+                entity_window.size = scene_window.size;
+            }
+            if entity_window.view_size != scene_window.view_size {
+                let view_size = scene_window.view_size;
+                entity_window
+                    .window
+                    .set_view(&View::from_rect(FloatRect::new(
+                        0.0,
+                        0.0,
+                        view_size.x as f32,
+                        view_size.y as f32,
+                    )));
+                entity_window.view_size = view_size;
+            }
+            if entity_window.position != scene_window.position {
+                entity_window.window.set_position(scene_window.position);
+                entity_window.position = scene_window.position;
+                entity_window.moved_at = Instant::now();
+            }
+
+            let window = &mut entity_window.window;
+            window.clear(scene_window.background);
+            for primitive in &scene_window.primitives {
+                draw_primitive(window, primitive, &self.font, &RenderStates::DEFAULT);
+            }
+            window.display();
+        }
+
+        if self.windows.len() > open_windows {
+            self.focus_input_window();
+        }
+    }
+
+    fn poll_events(&mut self) -> Vec<Event> {
+        let mut events = Vec::new();
+
+        // Every window's queue is drained, keys count whichever window has focus
+        for entity_window in &mut self.windows {
+            let input = entity_window.name == self.input_window;
+            while let Some(evt) = entity_window.window.poll_event() {
+                match evt {
+                    Event::Closed => {
+                        entity_window.window.close();
+                        self.closed.push(entity_window.name.clone());
+                    }
+                    Event::KeyPressed { .. } | Event::KeyReleased { .. } => events.push(evt),
+                    Event::LostFocus | Event::GainedFocus if input => events.push(evt),
+                    _ => {}
+                }
+            }
+        }
+        self.windows.retain(|window| window.window.is_open());
+
+        events
+    }
+
+    fn mouse_position(&self) -> Option<Vector2f> {
+        // The windows sit at their scene positions, so the desktop is the scene
+        let position = mouse::desktop_position();
+        Some(Vector2::new(position.x as f32, position.y as f32))
+    }
+
+    fn dragged_position(&mut self, name: &str) -> Option<Vector2i> {
+        let entity_window = self.windows.iter_mut().find(|window| window.name == name)?;
+        if entity_window.moved_at.elapsed() < SETTLE_TIME {
+            return None;
+        }
+
+        let position = entity_window.window.position();
+        if position == entity_window.position {
+            return None;
+        }
+        // Leave it where it was dragged to
+        entity_window.position = position;
+        Some(position)
+    }
+
+    fn closed_windows(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.closed)
+    }
+
+    fn set_input_window(&mut self, name: &str) {
+        if self.input_window != name {
+            self.input_window = name.to_string();
+            self.focus_input_window();
+        }
+    }
+}
+
+/// Draws a primitive with SFML, shared by every SFML backed renderer
+pub fn draw_primitive<T: RenderTarget>(
+    target: &mut T,
+    primitive: &Primitive,
+    font: &Font,
+    states: &RenderStates,
+) {
+    match primitive {
+        Primitive::Rect {
+            position,
+            size,
+            color,
+        } => {
+            let mut rect = RectangleShape::with_size(*size);
+            rect.set_position(*position);
+            rect.set_fill_color(*color);
+            target.draw_with_renderstates(&rect, states);
+        }
+        Primitive::Circle {
+            center,
+            radius,
+            color,
+        } => {
+            let mut circle = CircleShape::new(*radius, 30);
+            circle.set_origin(Vector2::new(*radius, *radius));
+            circle.set_position(*center);
+            circle.set_fill_color(*color);
+            target.draw_with_renderstates(&circle, states);
+        }
+        Primitive::Text {
+            string,
+            position,
+            size,
+            align,
+            color,
+        } => {
+            // Lay the lines out one by one so each can be aligned on its own
+            let line_spacing = font.line_spacing(*size);
+            let top = position.y - line_spacing * string.lines().count() as f32 / 2.0;
+
+            for (index, line) in string.lines().enumerate() {
+                let mut text = Text::new(line, font, *size);
+                text.set_fill_color(*color);
+                let bounds = text.local_bounds();
+                let origin_x = match align {
+                    Align::Left => bounds.left,
+                    Align::Center => bounds.left + bounds.width / 2.0,
+                    Align::Right => bounds.left + bounds.width,
+                };
+                text.set_origin(Vector2::new(origin_x, 0.0));
+                text.set_position(Vector2::new(position.x, top + line_spacing * index as f32));
+                target.draw_with_renderstates(&text, states);
+            }
+        }
+    }
+}
+
+/// Space a block of text takes up when drawn with `draw_primitive`
+pub fn measure_text(font: &Font, string: &str, size: u32) -> Vector2f {
+    let width = string
+        .lines()
+        .map(|line| {
+            let bounds = Text::new(line, font, size).local_bounds();
+            bounds.left + bounds.width
+        })
+        .fold(0.0, f32::max);
+
+    Vector2f::new(
+        width,
+        font.line_spacing(size) * string.lines().count() as f32,
+    )
+}
@@ -0,0 +1,355 @@
+//! Recording matches to replay files, one line per window and primitive of every frame
+//!
+//! ```text
+//! window-pong-replay 1 <desktop width> <desktop height>
+//! frame <milliseconds since the start>
+//! window <x> <y> <width> <height> <view width> <view height> <r> <g> <b> <a> <name>
+//! rect <x> <y> <width> <height> <r> <g> <b> <a>
+//! circle <x> <y> <radius> <r> <g> <b> <a>
+//! text <x> <y> <size> <left|center|right> <r> <g> <b> <a> <string>
+//! ```
+
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+    str::SplitWhitespace,
+    thread,
+    time::{Duration, Instant},
+};
+
+use sfml::{
+    graphics::Color,
+    system::{Vector2, Vector2f, Vector2i, Vector2u},
+    window::{Event, Key},
+};
+
+use crate::{
+    error::GameError,
+    render::{Align, Primitive, Renderer, Scene, SceneWindow},
+};
+
+const HEADER: &str = "window-pong-replay 1";
+
+pub struct Replay {
+    pub desktop_size: Vector2u,
+    // Each frame with the milliseconds since the start of the match it was shown at
+    pub frames: Vec<(u64, Scene)>,
+}
+
+/// Passes frames through to another renderer, writing them to a replay file as it goes
+pub struct ReplayRecorder {
+    inner: Box<dyn Renderer>,
+    output: Option<BufWriter<File>>,
+    start: Instant,
+}
+
+impl ReplayRecorder {
+    pub fn new(inner: Box<dyn Renderer>, output: BufWriter<File>) -> ReplayRecorder {
+        ReplayRecorder {
+            inner,
+            output: Some(output),
+            start: Instant::now(),
+        }
+    }
+}
+
+/// Starts a replay file for a desktop of the given size
+pub fn create(path: &Path, desktop_size: Vector2u) -> io::Result<BufWriter<File>> {
+    let mut output = BufWriter::new(File::create(path)?);
+    writeln!(output, "{} {} {}", HEADER, desktop_size.x, desktop_size.y)?;
+    Ok(output)
+}
+
+impl Renderer for ReplayRecorder {
+    fn render(&mut self, scene: &Scene) {
+        self.inner.render(scene);
+
+        if let Some(output) = self.output.as_mut() {
+            let timestamp = self.start.elapsed().as_millis() as u64;
+            if let Err(err) = write_frame(output, timestamp, scene) {
+                // Keep playing, the match just won't be saved
+                println!("Stopped recording the replay: {}", err);
+                self.output = None;
+            }
+        }
+    }
+
+    fn open(&mut self, scene: &Scene) -> Result<(), GameError> {
+        self.inner.open(scene)
+    }
+
+    fn poll_events(&mut self) -> Vec<Event> {
+        self.inner.poll_events()
+    }
+
+    fn mouse_position(&self) -> Option<Vector2f> {
+        self.inner.mouse_position()
+    }
+
+    fn dragged_position(&mut self, name: &str) -> Option<Vector2i> {
+        self.inner.dragged_position(name)
+    }
+
+    fn closed_windows(&mut self) -> Vec<String> {
+        self.inner.closed_windows()
+    }
+
+    fn set_input_window(&mut self, name: &str) {
+        self.inner.set_input_window(name)
+    }
+}
+
+fn write_color(output: &mut impl Write, color: Color) -> io::Result<()> {
+    write!(output, "{} {} {} {}", color.r, color.g, color.b, color.a)
+}
+
+fn escape(string: &str) -> String {
+    string.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape(string: &str) -> String {
+    let mut unescaped = String::with_capacity(string.len());
+    let mut chars = string.chars();
+    while let Some(character) = chars.next() {
+        if character == '\\' {
+            match chars.next() {
+                Some('n') => unescaped.push('\n'),
+                Some(other) => unescaped.push(other),
+                None => {}
+            }
+        } else {
+            unescaped.push(character);
+        }
+    }
+    unescaped
+}
+
+fn write_frame(output: &mut impl Write, timestamp: u64, scene: &Scene) -> io::Result<()> {
+    writeln!(output, "frame {}", timestamp)?;
+
+    for window in &scene.windows {
+        write!(
+            output,
+            "window {} {} {} {} {} {} ",
+            window.position.x,
+            window.position.y,
+            window.size.x,
+            window.size.y,
+            window.view_size.x,
+            window.view_size.y
+        )?;
+        write_color(output, window.background)?;
+        writeln!(output, " {}", escape(&window.name))?;
+
+        for primitive in &window.primitives {
+            match primitive {
+                Primitive::Rect {
+                    position,
+                    size,
+                    color,
+                } => {
+                    write!(
+                        output,
+                        "rect {} {} {} {} ",
+                        position.x, position.y, size.x, size.y
+                    )?;
+                    write_color(output, *color)?;
+                }
+                Primitive::Circle {
+                    center,
+                    radius,
+                    color,
+                } => {
+                    write!(output, "circle {} {} {} ", center.x, center.y, radius)?;
+                    write_color(output, *color)?;
+                }
+                Primitive::Text {
+                    string,
+                    position,
+                    size,
+                    align,
+                    color,
+                } => {
+                    let align = match align {
+                        Align::Left => "left",
+                        Align::Center => "center",
+                        Align::Right => "right",
+                    };
+                    write!(
+                        output,
+                        "text {} {} {} {} ",
+                        position.x, position.y, size, align
+                    )?;
+                    write_color(output, *color)?;
+                    write!(output, " {}", escape(string))?;
+                }
+            }
+            writeln!(output)?;
+        }
+    }
+
+    output.flush()
+}
+
+struct Fields<'a> {
+    words: SplitWhitespace<'a>,
+    line: usize,
+}
+
+impl Fields<'_> {
+    fn next<T: std::str::FromStr>(&mut self) -> Result<T, String> {
+        self.words
+            .next()
+            .and_then(|word| word.parse().ok())
+            .ok_or_else(|| format!("line {} is malformed", self.line))
+    }
+
+    fn color(&mut self) -> Result<Color, String> {
+        Ok(Color::rgba(
+            self.next()?,
+            self.next()?,
+            self.next()?,
+            self.next()?,
+        ))
+    }
+}
+
+/// Splits off the first `count` words of a line, returning the rest untouched
+fn split_words(line: &str, count: usize) -> (&str, &str) {
+    let mut end = 0;
+    for _ in 0..count {
+        let rest = &line[end..];
+        let start = end + (rest.len() - rest.trim_start().len());
+        end = start + line[start..].find(' ').unwrap_or(line.len() - start);
+    }
+    let rest = line.get(end + 1..).unwrap_or("");
+    (&line[..end], rest)
+}
+
+pub fn load(path: &Path) -> Result<Replay, String> {
+    let source = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let mut lines = source.lines().enumerate();
+
+    let header = lines.next().map(|(_, line)| line).unwrap_or("");
+    let desktop = header
+        .strip_prefix(HEADER)
+        .ok_or_else(|| String::from("not a window-pong replay"))?;
+    let mut fields = Fields {
+        words: desktop.split_whitespace(),
+        line: 1,
+    };
+    let desktop_size = Vector2::new(fields.next()?, fields.next()?);
+
+    let mut frames: Vec<(u64, Scene)> = Vec::new();
+
+    for (index, line) in lines {
+        let line_number = index + 1;
+        let kind = line.split_whitespace().next().unwrap_or("");
+        let malformed = || format!("line {} is malformed", line_number);
+
+        match kind {
+            "frame" => {
+                let mut fields = Fields {
+                    words: line[5..].split_whitespace(),
+                    line: line_number,
+                };
+                frames.push((
+                    fields.next()?,
+                    Scene {
+                        windows: Vec::new(),
+                    },
+                ));
+            }
+            "window" => {
+                let (numbers, name) = split_words(line, 11);
+                let mut fields = Fields {
+                    words: numbers[6..].split_whitespace(),
+                    line: line_number,
+                };
+                let window = SceneWindow {
+                    position: Vector2::new(fields.next()?, fields.next()?),
+                    size: Vector2::new(fields.next()?, fields.next()?),
+                    view_size: Vector2::new(fields.next()?, fields.next()?),
+                    background: fields.color()?,
+                    name: unescape(name),
+                    primitives: Vec::new(),
+                };
+                let (_, scene) = frames.last_mut().ok_or_else(malformed)?;
+                scene.windows.push(window);
+            }
+            "rect" | "circle" | "text" => {
+                let (numbers, string) = if kind == "text" {
+                    split_words(line, 9)
+                } else {
+                    (line, "")
+                };
+                let mut fields = Fields {
+                    words: numbers[kind.len()..].split_whitespace(),
+                    line: line_number,
+                };
+                let primitive = match kind {
+                    "rect" => Primitive::Rect {
+                        position: Vector2::new(fields.next()?, fields.next()?),
+                        size: Vector2::new(fields.next()?, fields.next()?),
+                        color: fields.color()?,
+                    },
+                    "circle" => Primitive::Circle {
+                        center: Vector2::new(fields.next()?, fields.next()?),
+                        radius: fields.next()?,
+                        color: fields.color()?,
+                    },
+                    _ => Primitive::Text {
+                        position: Vector2::new(fields.next()?, fields.next()?),
+                        size: fields.next()?,
+                        align: fields.next()?,
+                        color: fields.color()?,
+                        string: unescape(string),
+                    },
+                };
+                let window = frames
+                    .last_mut()
+                    .and_then(|(_, scene)| scene.windows.last_mut())
+                    .ok_or_else(malformed)?;
+                window.primitives.push(primitive);
+            }
+            "" => {}
+            _ => return Err(malformed()),
+        }
+    }
+
+    Ok(Replay {
+        desktop_size,
+        frames,
+    })
+}
+
+/// Shows a replay at the speed it was recorded, until it ends, escape is pressed or a window
+/// is closed
+pub fn play(replay: &Replay, renderer: &mut dyn Renderer) -> Result<(), GameError> {
+    if let Some((_, scene)) = replay.frames.first() {
+        renderer.open(scene)?;
+    }
+    let start = Instant::now();
+
+    for (timestamp, scene) in &replay.frames {
+        if let Some(wait) = Duration::from_millis(*timestamp).checked_sub(start.elapsed()) {
+            thread::sleep(wait);
+        }
+        renderer.render(scene);
+
+        for evt in renderer.poll_events() {
+            match evt {
+                Event::Closed
+                | Event::KeyPressed {
+                    code: Key::Escape, ..
+                } => return Ok(()),
+                _ => {}
+            }
+        }
+        if !renderer.closed_windows().is_empty() {
+            return Ok(());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use std::{path::PathBuf, time::Duration};
+
+use sfml::system::{Vector2, Vector2f, Vector2u};
+
+use crate::{render::Align, serve::Side};
+
+/// What the score board says and how its text is laid out
+pub struct ScoreBoardLayout {
+    // Font file to use instead of the built-in Roboto
+    pub font: Option<PathBuf>,
+    pub text_size: u32,
+    pub align: Align,
+    // Text with placeholders, `\n` starts a new line
+    pub template: String,
+    // Grow or shrink the window to fit the text
+    pub auto_resize: bool,
+    // Space between the text and the window edges
+    pub padding: u32,
+    pub player_name: String,
+    pub ai_name: String,
+}
+
+/// The match state the score board can show
+pub struct ScoreInfo {
+    pub score: (u8, u8),
+    // Whoever is about to serve, while the ball isn't in play
+    pub server: Option<Side>,
+    pub elapsed: Duration,
+    // Time left in a timed match, shown on its own line
+    pub clock: Option<String>,
+    // Announcement added as an extra line, like a broken record
+    pub notice: Option<String>,
+}
+
+impl ScoreBoardLayout {
+    /// Fills in the template, leaving out the `{server}` lines while the ball is in play
+    pub fn text(&self, info: &ScoreInfo) -> String {
+        let server = match info.server {
+            Some(Side::Player) => self.player_name.as_str(),
+            Some(Side::Ai) => self.ai_name.as_str(),
+            None => "",
+        };
+        let seconds = info.elapsed.as_secs();
+
+        self.template
+            .split("\\n")
+            .filter(|line| info.server.is_some() || !line.contains("{server}"))
+            .map(|line| {
+                line.replace("{score}", &format!("{} - {}", info.score.0, info.score.1))
+                    .replace("{player_score}", &info.score.0.to_string())
+                    .replace("{ai_score}", &info.score.1.to_string())
+                    .replace("{player}", &self.player_name)
+                    .replace("{ai}", &self.ai_name)
+                    .replace("{server}", server)
+                    .replace("{time}", &format!("{}:{:02}", seconds / 60, seconds % 60))
+                    .trim()
+                    .to_string()
+            })
+            .chain(info.clock.clone())
+            .chain(info.notice.clone())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Window size that fits text of `text_size` pixels, never smaller than `min_size`
+    pub fn fit(&self, text_size: Vector2f, min_size: Vector2u) -> Vector2u {
+        Vector2::new(
+            (text_size.x.ceil() as u32 + self.padding * 2).max(min_size.x),
+            (text_size.y.ceil() as u32 + self.padding * 2).max(min_size.y),
+        )
+    }
+}
@@ -0,0 +1,115 @@
+use std::str::FromStr;
+
+use rand::Rng;
+use sfml::system::Vector2;
+
+use crate::{entity::Entity, utils};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Side {
+    Player,
+    Ai,
+}
+
+/// Who serves after a point
+#[derive(Clone, Copy, PartialEq)]
+pub enum ServeRule {
+    Loser,
+    Alternate,
+}
+
+impl FromStr for ServeRule {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "loser" => Ok(ServeRule::Loser),
+            "alternate" => Ok(ServeRule::Alternate),
+            _ => Err(format!(
+                "unknown serve rule `{}` (expected loser or alternate)",
+                value
+            )),
+        }
+    }
+}
+
+const MAX_AIM_ANGLE: f32 = 45.0;
+const AIM_SPEED: f32 = 1.5;
+const MAX_AI_AIM_ANGLE: f32 = 30.0;
+// Gap between the server's paddle and the ball
+const SERVE_GAP: f32 = 20.0;
+
+pub struct Serve {
+    pub rule: ServeRule,
+    pub server: Side,
+    pub aiming: bool,
+    angle: f32,
+    ai_delay: u32,
+    ai_timer: u32,
+}
+
+impl Serve {
+    pub fn new(rule: ServeRule, ai_delay: u32) -> Serve {
+        Serve {
+            rule,
+            server: Side::Player,
+            aiming: false,
+            angle: 0.0,
+            ai_delay,
+            ai_timer: ai_delay,
+        }
+    }
+
+    /// The direction the ball will leave the server's paddle in
+    fn direction(&self) -> Vector2<f32> {
+        let forward = match self.server {
+            Side::Player => 1.0,
+            Side::Ai => -1.0,
+        };
+        let angle = self.angle.to_radians();
+        Vector2::new(angle.cos() * forward, angle.sin())
+    }
+
+    /// Holds the ball in front of the server's paddle, along the aim direction
+    pub fn place_ball(&self, ball: &mut Entity, server_paddle: &Entity) {
+        let distance = (server_paddle.half_size.x + ball.half_size.x) as f32 + SERVE_GAP;
+        let ball_pos = server_paddle.position + self.direction() * distance;
+        ball.set_position(Some(ball_pos.x), Some(ball_pos.y));
+    }
+
+    pub fn aim(&mut self, input: i8) {
+        self.angle = (self.angle + input as f32 * AIM_SPEED).clamp(-MAX_AIM_ANGLE, MAX_AIM_ANGLE);
+    }
+
+    /// Counts down the AI's serve delay, aiming as it starts, and returns true once it's ready
+    /// to serve
+    pub fn ai_ready(&mut self) -> bool {
+        if self.ai_timer == self.ai_delay {
+            let mut rng = utils::rng();
+            self.angle = rng.gen_range(-MAX_AI_AIM_ANGLE..MAX_AI_AIM_ANGLE);
+        }
+        if self.ai_timer > 0 {
+            self.ai_timer -= 1;
+            return false;
+        }
+        true
+    }
+
+    pub fn launch(&mut self, ball: &mut Entity, speed: f32) {
+        ball.velocity = self.direction() * speed;
+        self.aiming = false;
+        self.angle = 0.0;
+        self.ai_timer = self.ai_delay;
+    }
+
+    /// Picks the next server once a point has been conceded
+    pub fn next_server(&mut self, conceded: Side) {
+        self.server = match self.rule {
+            ServeRule::Loser => conceded,
+            ServeRule::Alternate => match self.server {
+                Side::Player => Side::Ai,
+                Side::Ai => Side::Player,
+            },
+        };
+    }
+}
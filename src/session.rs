@@ -0,0 +1,135 @@
+//! Window events, pausing and frame pacing, the same for every game loop
+
+use std::time::{Duration, Instant};
+
+use sfml::window::{Event, Key};
+
+use crate::{config::Config, game::ClosePolicy, input::Control, render::Renderer};
+
+/// When the match was paused, and whether it was for losing focus
+struct Pause {
+    since: Instant,
+    by_focus: bool,
+}
+
+pub struct Session<'a> {
+    config: &'a Config,
+    // Wait between frames, off to play as fast as possible
+    realtime: bool,
+    // Cleared once the player quits, or enough matches have been played
+    pub running: bool,
+    paused: Option<Pause>,
+    // When the match started, moved on by however long it's been paused
+    match_start: Instant,
+    last_update: Instant,
+    frame_duration: Duration,
+}
+
+impl Session<'_> {
+    pub fn new(config: &Config, realtime: bool) -> Session<'_> {
+        Session {
+            config,
+            realtime,
+            running: true,
+            paused: None,
+            match_start: Instant::now(),
+            last_update: Instant::now(),
+            frame_duration: Duration::from_secs_f64(1.0 / 60.0),
+        }
+    }
+
+    /// Handles quitting, pausing and closed windows, passing on the keys the game plays with
+    pub fn poll_events(&mut self, renderer: &mut dyn Renderer) -> Vec<Event> {
+        let mut events = Vec::new();
+        for evt in renderer.poll_events() {
+            match evt {
+                Event::Closed
+                | Event::KeyPressed {
+                    code: Key::Escape, ..
+                } => self.running = false,
+                // Mouse controls click around the desktop, so only keyboard play pauses for it
+                Event::LostFocus
+                    if self.config.pause_on_focus_loss
+                        && self.config.control == Control::Keyboard
+                        && self.paused.is_none() =>
+                {
+                    self.pause(true);
+                }
+                Event::GainedFocus if self.paused.as_ref().is_some_and(|pause| pause.by_focus) => {
+                    self.resume();
+                }
+                Event::KeyPressed { code: Key::P, .. } => {
+                    if self.paused.is_some() {
+                        self.resume();
+                    } else {
+                        self.pause(false);
+                    }
+                }
+                // Nothing to serve or shoot while paused
+                Event::KeyPressed {
+                    code: Key::Space, ..
+                }
+                | Event::KeyReleased {
+                    code: Key::Space, ..
+                } if self.paused.is_some() => {}
+                Event::KeyPressed { .. } | Event::KeyReleased { .. } => events.push(evt),
+                _ => {}
+            }
+        }
+
+        for name in renderer.closed_windows() {
+            match self.config.window_close {
+                ClosePolicy::Quit => self.running = false,
+                ClosePolicy::Pause if self.paused.is_none() => {
+                    println!("The {} window was closed, press P to carry on", name);
+                    self.pause(false);
+                }
+                _ => {}
+            }
+        }
+
+        events
+    }
+
+    fn pause(&mut self, by_focus: bool) {
+        self.paused = Some(Pause {
+            since: Instant::now(),
+            by_focus,
+        });
+    }
+
+    /// Carries on a paused match, leaving the pause out of the match time
+    fn resume(&mut self) {
+        if let Some(pause) = self.paused.take() {
+            self.match_start += pause.since.elapsed();
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.is_some()
+    }
+
+    /// Time played this match, not counting pauses
+    pub fn elapsed(&self) -> Duration {
+        match &self.paused {
+            Some(pause) => pause.since.duration_since(self.match_start),
+            None => self.match_start.elapsed(),
+        }
+    }
+
+    /// Starts timing the next match
+    pub fn restart_clock(&mut self) {
+        self.match_start = Instant::now();
+    }
+
+    /// Waits out the rest of a frame held for `frames` frames
+    pub fn wait(&mut self, frames: u32) {
+        if let (true, Some(sleep_duration)) = (
+            self.realtime,
+            (self.frame_duration * frames).checked_sub(self.last_update.elapsed()),
+        ) {
+            std::thread::sleep(sleep_duration);
+        }
+        self.last_update = Instant::now();
+    }
+}
@@ -0,0 +1,137 @@
+use std::str::FromStr;
+
+use crate::entity::Entity;
+
+/// How a paddle lines up a smash
+#[derive(Clone, Copy, PartialEq)]
+pub enum SmashMode {
+    /// Press shoot just before or just after the ball hits the paddle
+    Timing,
+    /// Hold shoot to charge, and release around the hit to smash with the stored power
+    Charge,
+}
+
+impl FromStr for SmashMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "timing" => Ok(SmashMode::Timing),
+            "charge" => Ok(SmashMode::Charge),
+            _ => Err(format!(
+                "unknown smash mode `{}` (expected timing or charge)",
+                value
+            )),
+        }
+    }
+}
+
+const MAX_SHOOT_TIMER: u8 = 10;
+const MAX_SHOOT_BUFFER: u8 = 10;
+// Power of an uncharged and a fully charged shot, as a multiple of the ball speed
+const MIN_CHARGE_POWER: f32 = 0.5;
+const MAX_CHARGE_POWER: f32 = 1.5;
+
+/// Tracks a paddle's smash timing window
+pub struct Shooter {
+    mode: SmashMode,
+    charge_frames: u32,
+    // Frames after a return in which shooting still smashes
+    timer: u8,
+    // Frames after shooting in which a return smashes
+    buffer: u8,
+    charge: f32,
+    charging: bool,
+    power: f32,
+}
+
+impl Shooter {
+    pub fn new(mode: SmashMode, charge_frames: u32) -> Shooter {
+        Shooter {
+            mode,
+            charge_frames: charge_frames.max(1),
+            timer: 0,
+            buffer: 0,
+            charge: 0.0,
+            charging: false,
+            power: 1.0,
+        }
+    }
+
+    pub fn mode(&self) -> SmashMode {
+        self.mode
+    }
+
+    /// How full the charge meter is, from 0 to 1
+    pub fn charge(&self) -> f32 {
+        self.charge
+    }
+
+    /// Shoot was pressed, returns the smash power if it was just late enough to smash
+    pub fn press(&mut self) -> Option<f32> {
+        match self.mode {
+            SmashMode::Timing => self.shoot(1.0),
+            SmashMode::Charge => {
+                self.charging = true;
+                None
+            }
+        }
+    }
+
+    /// Shoot was released, returns the smash power if it was just late enough to smash
+    pub fn release(&mut self) -> Option<f32> {
+        if self.mode != SmashMode::Charge || !self.charging {
+            return None;
+        }
+
+        let power = MIN_CHARGE_POWER + (MAX_CHARGE_POWER - MIN_CHARGE_POWER) * self.charge;
+        self.charging = false;
+        self.charge = 0.0;
+        self.shoot(power)
+    }
+
+    fn shoot(&mut self, power: f32) -> Option<f32> {
+        if self.timer > 0 {
+            self.timer = 0;
+            Some(power)
+        } else {
+            self.buffer = MAX_SHOOT_BUFFER;
+            self.power = power;
+            None
+        }
+    }
+
+    /// The ball hit the paddle, returns the smash power if shoot was buffered
+    pub fn on_return(&mut self) -> Option<f32> {
+        if self.buffer > 0 {
+            self.buffer = 0;
+            Some(self.power)
+        } else {
+            self.timer = MAX_SHOOT_TIMER;
+            None
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.timer = 0;
+        self.buffer = 0;
+        self.charge = 0.0;
+        self.charging = false;
+    }
+
+    pub fn update_timers(&mut self) {
+        self.buffer = self.buffer.saturating_sub(1);
+        self.timer = self.timer.saturating_sub(1);
+
+        if self.charging {
+            self.charge = (self.charge + 1.0 / self.charge_frames as f32).min(1.0);
+        }
+    }
+}
+
+/// Sends the ball straight across the screen at `speed`
+pub fn smash_ball(ball: &mut Entity, speed: f32) {
+    ball.velocity.x = speed * ball.velocity.x.signum();
+    ball.velocity.y = 0.0;
+    ball.color = ball.theme.smash;
+}
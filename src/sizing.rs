@@ -0,0 +1,86 @@
+use std::str::FromStr;
+
+use crate::entity::Entity;
+
+/// When paddles get shorter
+#[derive(Clone, Copy, PartialEq)]
+pub enum ShrinkRule {
+    Never,
+    OnHit,
+    OverTime,
+}
+
+impl FromStr for ShrinkRule {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "none" => Ok(ShrinkRule::Never),
+            "on_hit" => Ok(ShrinkRule::OnHit),
+            "over_time" => Ok(ShrinkRule::OverTime),
+            _ => Err(format!(
+                "unknown shrink rule `{}` (expected none, on_hit or over_time)",
+                value
+            )),
+        }
+    }
+}
+
+/// Paddle height rules, all amounts are fractions of the paddle's starting height
+#[derive(Clone)]
+pub struct PaddleSizing {
+    pub rule: ShrinkRule,
+    pub shrink_per_frame: f32,
+    pub shrink_per_hit: f32,
+    pub return_growth: f32,
+    pub miss_penalty: f32,
+    pub min_scale: f32,
+    pub max_scale: f32,
+}
+
+impl PaddleSizing {
+    fn resize(&self, paddle: &mut Entity, change: f32) {
+        if change == 0.0 {
+            return;
+        }
+
+        let scale = (paddle.scale.y + change).clamp(self.min_scale, self.max_scale);
+        paddle.set_scale_xy(None, Some(scale));
+    }
+
+    /// Called every frame while the ball is in play
+    pub fn on_tick(&self, paddle: &mut Entity) {
+        if self.rule == ShrinkRule::OverTime {
+            self.resize(paddle, -self.shrink_per_frame);
+        }
+    }
+
+    /// Called when the paddle returns the ball
+    pub fn on_return(&self, paddle: &mut Entity) {
+        let shrink = if self.rule == ShrinkRule::OnHit {
+            self.shrink_per_hit
+        } else {
+            0.0
+        };
+        self.resize(paddle, self.return_growth - shrink);
+    }
+
+    /// Called when a point is scored, `missed` being whether this paddle conceded it
+    pub fn on_point(&self, paddle: &mut Entity, missed: bool) {
+        paddle.set_scale(1.0);
+        if missed {
+            self.resize(paddle, -self.miss_penalty);
+        }
+    }
+
+    /// How far the paddle is between its smallest and largest size, from 0 to 1
+    pub fn meter(&self, paddle: &Entity) -> Option<f32> {
+        if self.max_scale <= self.min_scale {
+            return None;
+        }
+
+        Some(
+            ((paddle.scale.y - self.min_scale) / (self.max_scale - self.min_scale)).clamp(0.0, 1.0),
+        )
+    }
+}
@@ -0,0 +1,93 @@
+//! Golden image checks for offscreen rendered frames
+//!
+//! The committed images live in `tests/snapshots`, regenerate them with
+//! `UPDATE_SNAPSHOTS=1 cargo test snapshot`
+
+use std::{env, fs, path::PathBuf};
+
+use sfml::{graphics::Color, system::Vector2u};
+
+use crate::{
+    png,
+    render::{offscreen::OffscreenRenderer, Renderer, Scene},
+};
+
+// A channel off by more than this counts the pixel as different
+const CHANNEL_TOLERANCE: u8 = 8;
+// Fraction of pixels allowed to differ, to absorb float rounding in the rasterizer
+const PIXEL_TOLERANCE: f32 = 0.005;
+
+pub fn render(scene: &Scene, desktop_size: Vector2u, image_size: Vector2u) -> OffscreenRenderer {
+    let mut renderer = OffscreenRenderer::new(desktop_size, image_size, Color::BLACK);
+    renderer.render(scene);
+    renderer
+}
+
+/// Compares a rendered frame with `tests/snapshots/<name>.png`
+pub fn assert_snapshot(name: &str, renderer: &OffscreenRenderer) {
+    let snapshot_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("snapshots");
+    let snapshot_path = snapshot_dir.join(format!("{}.png", name));
+    let encoded = png::encode(renderer.image_size(), renderer.pixels());
+
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(&snapshot_dir).unwrap();
+        fs::write(&snapshot_path, encoded).unwrap();
+        return;
+    }
+
+    let expected = fs::read(&snapshot_path).unwrap_or_else(|_| {
+        panic!(
+            "Missing snapshot {}, run `UPDATE_SNAPSHOTS=1 cargo test snapshot` to create it",
+            snapshot_path.display()
+        )
+    });
+    let (expected_size, expected_pixels) = png::decode(&expected)
+        .unwrap_or_else(|err| panic!("Couldn't read {}: {}", snapshot_path.display(), err));
+
+    assert!(
+        expected_size == renderer.image_size(),
+        "Snapshot {} is {}x{} but the frame is {}x{}",
+        name,
+        expected_size.x,
+        expected_size.y,
+        renderer.image_size().x,
+        renderer.image_size().y
+    );
+
+    let differing = expected_pixels
+        .chunks_exact(4)
+        .zip(renderer.pixels().chunks_exact(4))
+        .filter(|(expected, actual)| {
+            expected
+                .iter()
+                .zip(actual.iter())
+                .any(|(e, a)| e.abs_diff(*a) > CHANNEL_TOLERANCE)
+        })
+        .count();
+    let pixel_count = (expected_size.x * expected_size.y) as f32;
+
+    if differing as f32 > pixel_count * PIXEL_TOLERANCE {
+        // Leave the frame next to the build output so it can be compared by eye
+        let actual_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("target")
+            .join("snapshots")
+            .join(format!("{}.actual.png", name));
+        let saved = fs::create_dir_all(actual_path.parent().unwrap())
+            .and_then(|_| fs::write(&actual_path, &encoded))
+            .is_ok();
+
+        panic!(
+            "Snapshot {} differs in {} of {} pixels{}",
+            name,
+            differing,
+            pixel_count,
+            if saved {
+                format!(", the new frame is at {}", actual_path.display())
+            } else {
+                String::new()
+            }
+        );
+    }
+}
@@ -0,0 +1,344 @@
+use std::{
+    env, fs,
+    io::{self, ErrorKind},
+    path::PathBuf,
+};
+
+use crate::{ai::Difficulty, serve::Side};
+
+/// Wins and losses against one AI difficulty
+#[derive(Clone, Copy, Default)]
+pub struct Record {
+    pub wins: u32,
+    pub losses: u32,
+}
+
+/// A named player's lifetime statistics, saved in the window-pong data directory
+pub struct Profile {
+    pub name: String,
+    pub matches_played: u32,
+    // Indexed like `Difficulty::ALL`
+    pub records: [Record; 3],
+    // Most times the player returned the ball in a single point
+    pub longest_rally: u32,
+    // In pixels per frame
+    pub fastest_ball: f32,
+    pub smashes: u32,
+}
+
+/// `$XDG_DATA_HOME/window-pong`, or `~/.local/share/window-pong`
+pub fn data_dir() -> Option<PathBuf> {
+    let data_dir = match env::var_os("XDG_DATA_HOME") {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env::var_os("HOME")?)
+            .join(".local")
+            .join("share"),
+    };
+
+    Some(data_dir.join("window-pong"))
+}
+
+fn profiles_dir() -> Option<PathBuf> {
+    Some(data_dir()?.join("profiles"))
+}
+
+/// Percent-encodes anything but letters, digits, `-` and `_`, so names can't reach outside the
+/// profiles directory and different names never share a file
+fn file_name(name: &str) -> String {
+    let mut encoded = String::new();
+    for character in name.chars() {
+        if character.is_alphanumeric() || character == '-' || character == '_' {
+            encoded.push(character);
+        } else {
+            let mut bytes = [0; 4];
+            for byte in character.encode_utf8(&mut bytes).bytes() {
+                encoded += &format!("%{:02X}", byte);
+            }
+        }
+    }
+    format!("{}.toml", encoded)
+}
+
+/// The name a profile file was saved under, undoing `file_name`
+fn profile_name(file_stem: &str) -> Option<String> {
+    let mut bytes = Vec::new();
+    let mut rest = file_stem.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let digits = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(digits, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+impl Profile {
+    pub fn new(name: &str) -> Profile {
+        Profile {
+            name: name.to_string(),
+            matches_played: 0,
+            records: [Record::default(); 3],
+            longest_rally: 0,
+            fastest_ball: 0.0,
+            smashes: 0,
+        }
+    }
+
+    /// Reads a profile, starting a fresh one if it hasn't been saved yet
+    pub fn load(name: &str) -> Result<Profile, String> {
+        let path = profiles_dir()
+            .ok_or_else(|| String::from("no data directory"))?
+            .join(file_name(name));
+        let source = match fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Profile::new(name)),
+            Err(err) => return Err(format!("{}: {}", path.display(), err)),
+        };
+
+        let mut profile = Profile::new(name);
+        for (index, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            profile
+                .set(line)
+                .map_err(|err| format!("{} line {}: {}", path.display(), index + 1, err))?;
+        }
+        Ok(profile)
+    }
+
+    fn set(&mut self, line: &str) -> Result<(), String> {
+        let (key, value) = line
+            .split_once('=')
+            .map(|(key, value)| (key.trim(), value.trim()))
+            .ok_or_else(|| format!("expected `key = value`, found `{}`", line))?;
+        let number = |value: &str| {
+            value
+                .parse::<u32>()
+                .map_err(|_| format!("`{}` isn't a number", value))
+        };
+
+        match key {
+            "name" => self.name = value.to_string(),
+            "matches_played" => self.matches_played = number(value)?,
+            "longest_rally" => self.longest_rally = number(value)?,
+            "smashes" => self.smashes = number(value)?,
+            "fastest_ball" => {
+                self.fastest_ball = value
+                    .parse()
+                    .map_err(|_| format!("`{}` isn't a number", value))?
+            }
+            _ => {
+                let Some((difficulty, field)) = key.split_once('.') else {
+                    return Err(format!("unknown stat `{}`", key));
+                };
+                let index = Difficulty::ALL
+                    .iter()
+                    .position(|known| known.name() == difficulty)
+                    .ok_or_else(|| format!("unknown stat `{}`", key))?;
+                match field {
+                    "wins" => self.records[index].wins = number(value)?,
+                    "losses" => self.records[index].losses = number(value)?,
+                    _ => return Err(format!("unknown stat `{}`", key)),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let dir = profiles_dir()
+            .ok_or_else(|| io::Error::new(ErrorKind::NotFound, "no data directory"))?;
+        fs::create_dir_all(&dir)?;
+
+        let mut contents = format!(
+            "name = {}\nmatches_played = {}\nlongest_rally = {}\nfastest_ball = {}\nsmashes = {}\n",
+            self.name, self.matches_played, self.longest_rally, self.fastest_ball, self.smashes
+        );
+        for (difficulty, record) in Difficulty::ALL.iter().zip(&self.records) {
+            contents += &format!(
+                "{0}.wins = {1}\n{0}.losses = {2}\n",
+                difficulty.name(),
+                record.wins,
+                record.losses
+            );
+        }
+
+        fs::write(dir.join(file_name(&self.name)), contents)
+    }
+
+    pub fn print(&self) {
+        println!("{}", self.name);
+        println!("  Matches played: {}", self.matches_played);
+        for (difficulty, record) in Difficulty::ALL.iter().zip(&self.records) {
+            println!(
+                "  Against {} AI: {} won, {} lost",
+                difficulty.name(),
+                record.wins,
+                record.losses
+            );
+        }
+        println!("  Longest rally: {} returns", self.longest_rally);
+        println!("  Fastest ball: {:.1} pixels per frame", self.fastest_ball);
+        println!("  Smashes: {}", self.smashes);
+    }
+}
+
+/// Feeds what happens in a match into the player's profile
+pub struct MatchStats {
+    profile: Profile,
+    difficulty: Difficulty,
+    rally: u32,
+    // Off when the saved profile couldn't be read, so it isn't overwritten
+    saving: bool,
+}
+
+impl MatchStats {
+    pub fn load(name: &str, difficulty: Difficulty) -> MatchStats {
+        let (profile, saving) = match Profile::load(name) {
+            Ok(profile) => (profile, true),
+            Err(err) => {
+                println!(
+                    "Couldn't read the stats for {}: {}, not saving them",
+                    name, err
+                );
+                (Profile::new(name), false)
+            }
+        };
+
+        MatchStats {
+            profile,
+            difficulty,
+            rally: 0,
+            saving,
+        }
+    }
+
+    /// Keeps track of a match without ever saving it, for simulated matches
+    pub fn unsaved(name: &str, difficulty: Difficulty) -> MatchStats {
+        MatchStats {
+            profile: Profile::new(name),
+            difficulty,
+            rally: 0,
+            saving: false,
+        }
+    }
+
+    /// Called whenever a paddle returns the ball, with the ball's speed afterwards. Only the
+    /// player's returns count towards their stats
+    pub fn on_return(&mut self, side: Side, ball_speed: f32) {
+        if side != Side::Player {
+            return;
+        }
+        self.rally += 1;
+        self.profile.longest_rally = self.profile.longest_rally.max(self.rally);
+        self.profile.fastest_ball = self.profile.fastest_ball.max(ball_speed);
+    }
+
+    pub fn on_smash(&mut self, side: Side, ball_speed: f32) {
+        if side != Side::Player {
+            return;
+        }
+        self.profile.smashes += 1;
+        self.profile.fastest_ball = self.profile.fastest_ball.max(ball_speed);
+    }
+
+    /// The player's returns in the current point
+    pub fn rally(&self) -> u32 {
+        self.rally
+    }
+
+    pub fn on_point(&mut self) {
+        self.rally = 0;
+    }
+
+    /// Records the result and saves the profile
+    pub fn on_match_end(&mut self, winner: Side) {
+        self.profile.matches_played += 1;
+
+        let index = Difficulty::ALL
+            .iter()
+            .position(|difficulty| *difficulty == self.difficulty)
+            .unwrap_or(0);
+        match winner {
+            Side::Player => self.profile.records[index].wins += 1,
+            Side::Ai => self.profile.records[index].losses += 1,
+        }
+
+        self.save();
+    }
+
+    pub fn save(&self) {
+        if !self.saving {
+            return;
+        }
+        if let Err(err) = self.profile.save() {
+            println!("Couldn't save the stats for {}: {}", self.profile.name, err);
+        }
+    }
+}
+
+/// Prints one profile, or every saved one
+pub fn print_stats(name: Option<&str>) -> Result<(), String> {
+    if let Some(name) = name {
+        Profile::load(name)?.print();
+        return Ok(());
+    }
+
+    let dir = profiles_dir().ok_or_else(|| String::from("no data directory"))?;
+    let mut names: Vec<String> = match fs::read_dir(&dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                if path.extension()? != "toml" {
+                    return None;
+                }
+                profile_name(path.file_stem()?.to_str()?)
+            })
+            .collect(),
+        Err(err) if err.kind() == ErrorKind::NotFound => Vec::new(),
+        Err(err) => return Err(format!("{}: {}", dir.display(), err)),
+    };
+    names.sort();
+
+    if names.is_empty() {
+        println!("No stats yet, play a match first");
+    }
+    for name in names {
+        match Profile::load(&name) {
+            Ok(profile) => profile.print(),
+            Err(err) => println!("Couldn't read the stats for {}: {}", name, err),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{file_name, profile_name};
+
+    #[test]
+    fn names_get_files_of_their_own() {
+        assert_eq!(file_name("Sam!"), "Sam%21.toml");
+        assert_ne!(file_name("Sam!"), file_name("Sam?"));
+        assert_ne!(file_name("Sam_"), file_name("Sam?"));
+        assert_eq!(file_name("../Zoë"), "%2E%2E%2FZoë.toml");
+    }
+
+    #[test]
+    fn file_names_decode_back() {
+        for name in ["Sam", "Sam!", "../a b", "Zoë 100%"] {
+            let file = file_name(name);
+            assert_eq!(
+                profile_name(file.trim_end_matches(".toml")),
+                Some(name.into())
+            );
+        }
+        assert_eq!(profile_name("Sam%2"), None);
+    }
+}
@@ -0,0 +1,116 @@
+use std::str::FromStr;
+
+use sfml::graphics::Color;
+
+/// Every color the game draws with
+#[derive(Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub player: Color,
+    pub ai: Color,
+    // Also what paddles flash to when they return the ball
+    pub ball: Color,
+    pub score_board: Color,
+    pub score_text: Color,
+    // Smashed balls and the paddles that hit them
+    pub smash: Color,
+    pub eye: Color,
+    pub pupil: Color,
+    pub smash_pupil: Color,
+    // Charge meter filling up a paddle, see-through over the paddle's color
+    pub charge: Color,
+    // Bar along the bottom of a paddle showing how far it's grown or shrunk
+    pub size_meter: Color,
+    // Strip along the edge of the desktop when a point is conceded
+    pub flash: Color,
+    // Desktop behind the windows, when they're all drawn in one window or captured
+    pub backdrop: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            player: Color::BLUE,
+            ai: Color::RED,
+            ball: Color::WHITE,
+            score_board: Color::WHITE,
+            score_text: Color::BLACK,
+            smash: Color::YELLOW,
+            eye: Color::WHITE,
+            pupil: Color::BLACK,
+            smash_pupil: Color::GREEN,
+            charge: Color::rgba(255, 255, 0, 160),
+            size_meter: Color::rgba(255, 255, 255, 200),
+            flash: Color::rgb(255, 60, 60),
+            backdrop: Color::rgb(30, 30, 30),
+        }
+    }
+}
+
+impl FromStr for Theme {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "classic" => Ok(Theme::default()),
+            "high_contrast" => Ok(Theme {
+                player: Color::rgb(0, 255, 255),
+                ai: Color::rgb(255, 0, 255),
+                ball: Color::WHITE,
+                score_board: Color::BLACK,
+                score_text: Color::WHITE,
+                smash: Color::YELLOW,
+                eye: Color::WHITE,
+                pupil: Color::BLACK,
+                smash_pupil: Color::BLACK,
+                charge: Color::rgba(255, 255, 0, 200),
+                size_meter: Color::WHITE,
+                flash: Color::rgb(255, 0, 0),
+                backdrop: Color::BLACK,
+            }),
+            // Okabe-Ito colors, told apart with any kind of color blindness
+            "colorblind" => Ok(Theme {
+                player: Color::rgb(0, 114, 178),
+                ai: Color::rgb(230, 159, 0),
+                ball: Color::WHITE,
+                score_board: Color::WHITE,
+                score_text: Color::BLACK,
+                smash: Color::rgb(240, 228, 66),
+                eye: Color::WHITE,
+                pupil: Color::BLACK,
+                smash_pupil: Color::rgb(204, 121, 167),
+                charge: Color::rgba(240, 228, 66, 160),
+                size_meter: Color::rgba(255, 255, 255, 200),
+                flash: Color::rgb(213, 94, 0),
+                backdrop: Color::rgb(30, 30, 30),
+            }),
+            _ => Err(format!(
+                "unknown theme `{}` (expected classic, high_contrast or colorblind)",
+                value
+            )),
+        }
+    }
+}
+
+/// Parses `#rrggbb` colors, or `#rrggbbaa` for see-through ones
+pub fn parse_color(key: &str, value: &str) -> Result<Color, String> {
+    let error = || {
+        format!(
+            "`{}` expects a color like #ff8800 or #ff880080, found `{}`",
+            key, value
+        )
+    };
+
+    let hex = value.strip_prefix('#').ok_or_else(error)?;
+    if hex.len() != 6 && hex.len() != 8 {
+        return Err(error());
+    }
+    let channel = |index: usize| {
+        hex.get(index..index + 2)
+            .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+            .ok_or_else(error)
+    };
+
+    let alpha = if hex.len() == 8 { channel(6)? } else { 255 };
+
+    Ok(Color::rgba(channel(0)?, channel(2)?, channel(4)?, alpha))
+}
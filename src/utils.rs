@@ -1,5 +1,46 @@
+use std::cell::RefCell;
+
+use rand::{rngs::StdRng, RngCore, SeedableRng};
 use sfml::system::Vector2f;
 
+thread_local! {
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+/// Makes every random choice in the game repeat for the same seed
+pub fn seed_rng(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+/// The game's random number generator, seeded with `seed_rng` or from the OS
+pub fn rng() -> GameRng {
+    GameRng
+}
+
+pub struct GameRng;
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        RNG.with(|rng| rng.borrow_mut().next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        RNG.with(|rng| rng.borrow_mut().next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        RNG.with(|rng| rng.borrow_mut().fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        RNG.with(|rng| rng.borrow_mut().try_fill_bytes(dest))
+    }
+}
+
+pub fn vector_length(vector: Vector2f) -> f32 {
+    (vector.x.powi(2) + vector.y.powi(2)).sqrt()
+}
+
 /// Normalizes a vector
 pub fn normalize_vector(vector: Vector2f) -> Vector2f {
     let length = (vector.x.powi(2) + vector.y.powi(2)).sqrt();